@@ -1,15 +1,407 @@
-use clap::Parser;
+use crate::cli::columns::{Column, DEFAULT_COLUMNS, Details, TimeStyle};
+use crate::cli::output::OutputFormat;
+use crate::cli::shell::Shell;
+use crate::cli::terminal::{DEFAULT_INLINE_HEIGHT, Height, Screen};
+use crate::core::explorer::PathMode;
+use crate::core::navigation::{AutoCd, DEFAULT_AUTO_CD_DELAY};
+use crate::fs::cache::DEFAULT_CAPACITY_MIB;
+use crate::fs::traversal::{DEFAULT_EXCLUDE, DEFAULT_MAX_DEPTH};
+use crate::fs::{ListOptions, SortKey, SortOrder, WalkOptions};
+use clap::builder::RangedU64ValueParser;
+use clap::{Parser, Subcommand};
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "breeze")]
 #[command(about = "A terminal-based file explorer")]
 pub struct Args {
-    /// Directory to explore
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+
+    /// Directory to explore; write `./init` or `-- init` for a directory named like the subcommand
     #[arg(default_value = ".")]
     pub directory: PathBuf,
 
     /// Show hidden files
     #[arg(short, long)]
     pub all: bool,
+
+    /// Order of the listing
+    #[arg(long, value_enum, default_value_t = SortKey::Name)]
+    pub sort: SortKey,
+
+    /// Reverse the sort order
+    #[arg(short, long)]
+    pub reverse: bool,
+
+    /// List directories before files
+    #[arg(long)]
+    pub dirs_first: bool,
+
+    /// Keep symlinks in the current directory's path instead of resolving them, like `cd -L`
+    #[arg(short = 'L', long, overrides_with = "physical")]
+    pub logical: bool,
+
+    /// Resolve symlinks in the current directory's path, like `cd -P` (default)
+    #[arg(short = 'P', long, overrides_with = "logical")]
+    pub physical: bool,
+
+    /// Memory in MiB for remembering directory listings between visits; 0 disables the cache
+    #[arg(long, value_name = "MIB", default_value_t = DEFAULT_CAPACITY_MIB)]
+    pub cache_size: usize,
+
+    /// Show detail columns in front of each name, like `ls -l`
+    #[arg(short, long)]
+    pub long: bool,
+
+    /// Detail columns to show with --long, in order
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = DEFAULT_COLUMNS)]
+    pub columns: Vec<Column>,
+
+    /// How --long shows modification times
+    #[arg(long, value_enum, default_value_t = TimeStyle::Relative)]
+    pub time_style: TimeStyle,
+
+    /// How many levels below the current directory the recursive search (`f`) looks
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_DEPTH, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub search_depth: usize,
+
+    /// Names the recursive search skips, comma-separated
+    #[arg(long, value_name = "NAMES", value_delimiter = ',', default_values_t = DEFAULT_EXCLUDE.map(String::from))]
+    pub search_exclude: Vec<String>,
+
+    /// Draw below the prompt in HEIGHT rows, or HEIGHT% of the terminal, instead of full screen
+    #[arg(long, value_name = "HEIGHT")]
+    pub height: Option<Height>,
+
+    /// Draw below the prompt instead of full screen (40% of the terminal unless --height is given)
+    #[arg(long)]
+    pub inline: bool,
+
+    /// Read NUL-separated instead of newline-separated paths from stdin
+    #[arg(long)]
+    pub read0: bool,
+
+    /// Encoding used to write the result to stdout
+    #[arg(long, value_enum, default_value_t = OutputFormat::Plain)]
+    pub output: OutputFormat,
+
+    /// Terminate every output field with a NUL byte (same as `--output null`)
+    #[arg(short = '0', long = "null", conflicts_with = "output")]
+    pub null: bool,
+
+    /// Write the final directory to PATH instead of printing the result to stdout
+    #[arg(long, value_name = "PATH")]
+    pub cwd_file: Option<PathBuf>,
+
+    /// Write the selected paths to PATH instead of printing the result to stdout
+    #[arg(long, value_name = "PATH")]
+    pub selection_file: Option<PathBuf>,
+
+    /// Restore directory, filter and selection from SESSION and save them back on exit
+    #[arg(long, value_name = "SESSION")]
+    pub resume: Option<PathBuf>,
+
+    /// Don't enter a directory automatically when the filter leaves it as the only match
+    #[arg(long)]
+    pub no_auto_cd: bool,
+
+    /// Milliseconds to wait for more typing before entering a single matching directory
+    #[arg(long, value_name = "MS", default_value_t = DEFAULT_AUTO_CD_DELAY.as_millis() as u64)]
+    pub auto_cd_delay: u64,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Print a shell function that runs breeze and applies its result
+    Init {
+        /// Shell to generate the integration for
+        #[arg(value_enum)]
+        shell: Shell,
+
+        /// Name of the generated wrapper function
+        #[arg(long, default_value = "bz")]
+        cmd: String,
+    },
+}
+
+impl Args {
+    /// Whether the result goes to `--cwd-file`/`--selection-file`, leaving
+    /// stdout untouched.
+    pub fn writes_result_to_files(&self) -> bool {
+        self.cwd_file.is_some() || self.selection_file.is_some()
+    }
+
+    pub fn list_options(&self) -> ListOptions {
+        ListOptions {
+            show_hidden: self.all,
+            sort: SortOrder {
+                key: self.sort,
+                reverse: self.reverse,
+                dirs_first: self.dirs_first,
+            },
+        }
+    }
+
+    pub fn path_mode(&self) -> PathMode {
+        if self.logical {
+            PathMode::Logical
+        } else {
+            PathMode::Physical
+        }
+    }
+
+    pub fn details(&self) -> Details {
+        Details::new(self.columns.clone(), self.time_style)
+    }
+
+    /// Options for the recursive search; hidden files follow the listing.
+    pub fn walk_options(&self) -> WalkOptions {
+        WalkOptions {
+            max_depth: self.search_depth,
+            exclude: self.search_exclude.iter().map(Into::into).collect(),
+            show_hidden: self.all,
+        }
+    }
+
+    pub fn screen(&self) -> Screen {
+        match (self.height, self.inline) {
+            (Some(height), _) => Screen::Inline(height),
+            (None, true) => Screen::Inline(DEFAULT_INLINE_HEIGHT),
+            (None, false) => Screen::Fullscreen,
+        }
+    }
+
+    /// Bound on the listing cache, in bytes.
+    pub fn cache_capacity(&self) -> usize {
+        self.cache_size.saturating_mul(1 << 20)
+    }
+
+    pub fn auto_cd(&self) -> Option<AutoCd> {
+        (!self.no_auto_cd).then(|| AutoCd::new(Duration::from_millis(self.auto_cd_delay)))
+    }
+
+    /// Byte separating the paths read from stdin in picker mode.
+    pub fn input_separator(&self) -> u8 {
+        if self.read0 { b'\0' } else { b'\n' }
+    }
+
+    pub fn output_format(&self) -> OutputFormat {
+        if self.null {
+            OutputFormat::Null
+        } else {
+            self.output
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_format_defaults_to_plain() {
+        let args = Args::parse_from(["breeze"]);
+        assert_eq!(args.output_format(), OutputFormat::Plain);
+    }
+
+    #[test]
+    fn test_output_format_null_short_flag() {
+        let args = Args::parse_from(["breeze", "-0"]);
+        assert_eq!(args.output_format(), OutputFormat::Null);
+    }
+
+    #[test]
+    fn test_output_format_long_option() {
+        let args = Args::parse_from(["breeze", "--output", "shell"]);
+        assert_eq!(args.output_format(), OutputFormat::Shell);
+    }
+
+    #[test]
+    fn test_output_format_json() {
+        let args = Args::parse_from(["breeze", "--output", "json"]);
+        assert_eq!(args.output_format(), OutputFormat::Json);
+    }
+
+    #[test]
+    fn test_init_subcommand() {
+        let args = Args::parse_from(["breeze", "init", "zsh"]);
+        match args.command {
+            Some(Commands::Init { shell, cmd }) => {
+                assert_eq!(shell, Shell::Zsh);
+                assert_eq!(cmd, "bz");
+            }
+            None => panic!("Expected init subcommand"),
+        }
+    }
+
+    #[test]
+    fn test_directory_without_subcommand() {
+        let args = Args::parse_from(["breeze", "/tmp"]);
+        assert!(args.command.is_none());
+        assert_eq!(args.directory, PathBuf::from("/tmp"));
+    }
+
+    #[test]
+    fn test_directory_named_like_subcommand() {
+        let args = Args::parse_from(["breeze", "./init"]);
+        assert!(args.command.is_none());
+        assert_eq!(args.directory, PathBuf::from("./init"));
+
+        let args = Args::parse_from(["breeze", "--", "init"]);
+        assert!(args.command.is_none());
+        assert_eq!(args.directory, PathBuf::from("init"));
+    }
+
+    #[test]
+    fn test_result_files() {
+        let args = Args::parse_from(["breeze"]);
+        assert!(!args.writes_result_to_files());
+
+        let args = Args::parse_from(["breeze", "--cwd-file", "/tmp/cwd"]);
+        assert_eq!(args.cwd_file, Some(PathBuf::from("/tmp/cwd")));
+        assert!(args.writes_result_to_files());
+
+        let args = Args::parse_from(["breeze", "--selection-file", "/tmp/sel"]);
+        assert_eq!(args.selection_file, Some(PathBuf::from("/tmp/sel")));
+        assert!(args.writes_result_to_files());
+    }
+
+    #[test]
+    fn test_list_options() {
+        let args = Args::parse_from(["breeze"]);
+        assert_eq!(args.list_options(), ListOptions::default());
+
+        let args = Args::parse_from([
+            "breeze",
+            "-a",
+            "--sort",
+            "ignore-case",
+            "-r",
+            "--dirs-first",
+        ]);
+        let options = args.list_options();
+        assert!(options.show_hidden);
+        assert_eq!(
+            options.sort,
+            SortOrder {
+                key: SortKey::IgnoreCase,
+                reverse: true,
+                dirs_first: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_path_mode() {
+        let args = Args::parse_from(["breeze"]);
+        assert_eq!(args.path_mode(), PathMode::Physical);
+
+        let args = Args::parse_from(["breeze", "-L"]);
+        assert_eq!(args.path_mode(), PathMode::Logical);
+
+        let args = Args::parse_from(["breeze", "-L", "-P"]);
+        assert_eq!(args.path_mode(), PathMode::Physical);
+
+        let args = Args::parse_from(["breeze", "-P", "--logical"]);
+        assert_eq!(args.path_mode(), PathMode::Logical);
+    }
+
+    #[test]
+    fn test_detail_columns() {
+        let args = Args::parse_from(["breeze"]);
+        assert!(!args.long);
+        assert_eq!(args.details().columns, DEFAULT_COLUMNS);
+
+        let args = Args::parse_from([
+            "breeze",
+            "-l",
+            "--columns",
+            "size,mtime",
+            "--time-style",
+            "absolute",
+        ]);
+        assert!(args.long);
+        let details = args.details();
+        assert_eq!(details.columns, vec![Column::Size, Column::Mtime]);
+        assert_eq!(details.time_style, TimeStyle::Absolute);
+    }
+
+    #[test]
+    fn test_walk_options() {
+        let args = Args::parse_from(["breeze"]);
+        assert_eq!(args.walk_options(), WalkOptions::default());
+
+        let args = Args::parse_from([
+            "breeze",
+            "--search-depth",
+            "3",
+            "--search-exclude",
+            "build,dist",
+        ]);
+        let options = args.walk_options();
+        assert_eq!(options.max_depth, 3);
+        assert_eq!(options.exclude, vec!["build", "dist"]);
+
+        assert!(Args::try_parse_from(["breeze", "--search-depth", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_screen() {
+        let args = Args::parse_from(["breeze"]);
+        assert_eq!(args.screen(), Screen::Fullscreen);
+
+        let args = Args::parse_from(["breeze", "--inline"]);
+        assert_eq!(args.screen(), Screen::Inline(DEFAULT_INLINE_HEIGHT));
+
+        let args = Args::parse_from(["breeze", "--height", "15"]);
+        assert_eq!(args.screen(), Screen::Inline(Height::Rows(15)));
+
+        let args = Args::parse_from(["breeze", "--inline", "--height", "50%"]);
+        assert_eq!(args.screen(), Screen::Inline(Height::Percent(50)));
+
+        assert!(Args::try_parse_from(["breeze", "--height", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_auto_cd_options() {
+        let now = std::time::Instant::now();
+        let args = Args::parse_from(["breeze"]);
+        let mut auto_cd = args.auto_cd().unwrap();
+        auto_cd.update([crate::fs::ObjectType::Directory], now);
+        assert_eq!(auto_cd.timeout(now), Some(DEFAULT_AUTO_CD_DELAY));
+
+        let args = Args::parse_from(["breeze", "--auto-cd-delay", "0"]);
+        let mut auto_cd = args.auto_cd().unwrap();
+        auto_cd.update([crate::fs::ObjectType::Directory], now);
+        assert!(auto_cd.take_due(now));
+
+        let args = Args::parse_from(["breeze", "--no-auto-cd"]);
+        assert!(args.auto_cd().is_none());
+    }
+
+    #[test]
+    fn test_cache_capacity() {
+        let args = Args::parse_from(["breeze"]);
+        assert_eq!(args.cache_capacity(), DEFAULT_CAPACITY_MIB << 20);
+
+        let args = Args::parse_from(["breeze", "--cache-size", "0"]);
+        assert_eq!(args.cache_capacity(), 0);
+    }
+
+    #[test]
+    fn test_input_separator() {
+        let args = Args::parse_from(["breeze"]);
+        assert_eq!(args.input_separator(), b'\n');
+
+        let args = Args::parse_from(["breeze", "--read0"]);
+        assert_eq!(args.input_separator(), b'\0');
+    }
+
+    #[test]
+    fn test_output_format_null_conflicts_with_output() {
+        let result = Args::try_parse_from(["breeze", "-0", "--output", "lines"]);
+        assert!(result.is_err());
+    }
 }
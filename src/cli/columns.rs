@@ -0,0 +1,317 @@
+//! Detail columns for the long listing (`-l`).
+//!
+//! Columns are laid out in front of the name, as `ls -l` does, so a long name
+//! is clipped at the right edge instead of pushing the details off screen.
+//! When the terminal is too narrow, columns are dropped from the end of the
+//! user's list until the name has room again.
+
+use crate::fs::{Entry, FileType};
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use std::time::{Duration, SystemTime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Column {
+    /// Type and permission bits, e.g. `drwxr-xr-x`
+    Permissions,
+    /// Owning user and group
+    Owner,
+    /// Human-readable size
+    Size,
+    /// Modification time
+    Mtime,
+}
+
+pub const DEFAULT_COLUMNS: [Column; 4] = [
+    Column::Permissions,
+    Column::Owner,
+    Column::Size,
+    Column::Mtime,
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum TimeStyle {
+    /// How long ago, e.g. `5m ago`
+    #[default]
+    Relative,
+    /// Local date and time, e.g. `2024-05-01 13:37`
+    Absolute,
+}
+
+/// Width the name keeps before detail columns are dropped.
+pub const MIN_NAME_WIDTH: usize = 16;
+
+pub struct Details {
+    pub columns: Vec<Column>,
+    pub time_style: TimeStyle,
+    owners: OwnerNames,
+}
+
+impl Details {
+    pub fn new(columns: Vec<Column>, time_style: TimeStyle) -> Self {
+        Self {
+            columns,
+            time_style,
+            owners: OwnerNames::default(),
+        }
+    }
+
+    /// Formats the detail prefix of every row for a list `width` cells wide.
+    ///
+    /// Rows without an entry get blank cells, so every prefix has the same
+    /// width.
+    pub fn rows<'e, I>(&self, entries: I, width: usize, now: SystemTime) -> Vec<String>
+    where
+        I: IntoIterator<Item = Option<&'e Entry>>,
+    {
+        let entries: Vec<Option<&Entry>> = entries.into_iter().collect();
+        let mut cells: Vec<(Column, Vec<String>, usize)> = Vec::new();
+        for &column in &self.columns {
+            let column_cells: Vec<String> = entries
+                .iter()
+                .map(|entry| entry.map_or_else(String::new, |e| self.cell(column, e, now)))
+                .collect();
+            let column_width = column_cells
+                .iter()
+                .map(|c| c.chars().count())
+                .max()
+                .unwrap_or(0);
+            cells.push((column, column_cells, column_width));
+        }
+
+        while !cells.is_empty()
+            && cells.iter().map(|(_, _, w)| w + 1).sum::<usize>() + MIN_NAME_WIDTH > width
+        {
+            cells.pop();
+        }
+
+        (0..entries.len())
+            .map(|row| {
+                let mut line = String::new();
+                for (column, column_cells, column_width) in &cells {
+                    let cell = &column_cells[row];
+                    match column {
+                        Column::Size => line.push_str(&format!("{cell:>column_width$} ")),
+                        _ => line.push_str(&format!("{cell:<column_width$} ")),
+                    }
+                }
+                line
+            })
+            .collect()
+    }
+
+    fn cell(&self, column: Column, entry: &Entry, now: SystemTime) -> String {
+        match column {
+            Column::Permissions => permission_string(entry),
+            Column::Owner => self.owners.owner(entry),
+            Column::Size if entry.file_type == FileType::Directory => "-".to_string(),
+            Column::Size => human_size(entry.size),
+            Column::Mtime => match (entry.modified, self.time_style) {
+                (None, _) => "?".to_string(),
+                (Some(modified), TimeStyle::Relative) => relative_time(modified, now),
+                (Some(modified), TimeStyle::Absolute) => DateTime::<Local>::from(modified)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string(),
+            },
+        }
+    }
+}
+
+impl Default for Details {
+    fn default() -> Self {
+        Self::new(DEFAULT_COLUMNS.to_vec(), TimeStyle::default())
+    }
+}
+
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "K", "M", "G", "T", "P"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}B", bytes)
+    } else if value < 10.0 {
+        format!("{:.1}{}", value, UNITS[unit])
+    } else {
+        format!("{:.0}{}", value, UNITS[unit])
+    }
+}
+
+pub fn relative_time(time: SystemTime, now: SystemTime) -> String {
+    let age = now.duration_since(time).unwrap_or(Duration::ZERO).as_secs();
+    match age {
+        0..60 => "just now".to_string(),
+        60..3_600 => format!("{}m ago", age / 60),
+        3_600..86_400 => format!("{}h ago", age / 3_600),
+        86_400..2_592_000 => format!("{}d ago", age / 86_400),
+        2_592_000..31_536_000 => format!("{}mo ago", age / 2_592_000),
+        _ => format!("{}y ago", age / 31_536_000),
+    }
+}
+
+pub fn permission_string(entry: &Entry) -> String {
+    let type_char = match entry.file_type {
+        FileType::Directory => 'd',
+        FileType::Symlink => 'l',
+        FileType::File => '-',
+        FileType::Other => '?',
+    };
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = entry.permissions.mode();
+        let mut s = String::with_capacity(10);
+        s.push(type_char);
+        for (shift, special, special_char) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')]
+        {
+            let bits = (mode >> shift) & 0o7;
+            s.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+            s.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+            s.push(match (bits & 0o1 != 0, mode & special != 0) {
+                (true, true) => special_char,
+                (false, true) => special_char.to_ascii_uppercase(),
+                (true, false) => 'x',
+                (false, false) => '-',
+            });
+        }
+        s
+    }
+
+    #[cfg(not(unix))]
+    {
+        let write = if entry.permissions.readonly() {
+            '-'
+        } else {
+            'w'
+        };
+        format!("{type_char}r{write}")
+    }
+}
+
+/// Cached uid/gid to name lookups.
+#[derive(Default)]
+struct OwnerNames {
+    #[cfg(unix)]
+    cache: uzers::UsersCache,
+}
+
+impl OwnerNames {
+    fn owner(&self, entry: &Entry) -> String {
+        match (entry.uid, entry.gid) {
+            (Some(uid), Some(gid)) => format!("{} {}", self.user(uid), self.group(gid)),
+            _ => String::new(),
+        }
+    }
+
+    #[cfg(unix)]
+    fn user(&self, uid: u32) -> String {
+        use uzers::Users;
+        self.cache.get_user_by_uid(uid).map_or_else(
+            || uid.to_string(),
+            |u| u.name().to_string_lossy().to_string(),
+        )
+    }
+
+    #[cfg(unix)]
+    fn group(&self, gid: u32) -> String {
+        use uzers::Groups;
+        self.cache.get_group_by_gid(gid).map_or_else(
+            || gid.to_string(),
+            |g| g.name().to_string_lossy().to_string(),
+        )
+    }
+
+    #[cfg(not(unix))]
+    fn user(&self, uid: u32) -> String {
+        uid.to_string()
+    }
+
+    #[cfg(not(unix))]
+    fn group(&self, gid: u32) -> String {
+        gid.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(0), "0B");
+        assert_eq!(human_size(1023), "1023B");
+        assert_eq!(human_size(1024), "1.0K");
+        assert_eq!(human_size(1536), "1.5K");
+        assert_eq!(human_size(20 * 1024 * 1024), "20M");
+        assert_eq!(human_size(3 * 1024 * 1024 * 1024), "3.0G");
+    }
+
+    #[test]
+    fn test_relative_time() {
+        let now = SystemTime::now();
+        let ago = |secs| now - Duration::from_secs(secs);
+        assert_eq!(relative_time(ago(5), now), "just now");
+        assert_eq!(relative_time(ago(300), now), "5m ago");
+        assert_eq!(relative_time(ago(7_200), now), "2h ago");
+        assert_eq!(relative_time(ago(3 * 86_400), now), "3d ago");
+        assert_eq!(relative_time(ago(400 * 86_400), now), "1y ago");
+        assert_eq!(
+            relative_time(now + Duration::from_secs(60), now),
+            "just now"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_permission_string() {
+        use std::os::unix::fs::PermissionsExt;
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("script.sh");
+        fs::write(&file, "").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o754)).unwrap();
+
+        let entry = Entry::from_path(&file).unwrap();
+        assert_eq!(permission_string(&entry), "-rwxr-xr--");
+
+        fs::set_permissions(temp_dir.path(), fs::Permissions::from_mode(0o1777)).unwrap();
+        let entry = Entry::from_path(temp_dir.path()).unwrap();
+        assert_eq!(permission_string(&entry), "drwxrwxrwt");
+    }
+
+    #[test]
+    fn test_rows_align_columns() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("small"), "a").unwrap();
+        fs::write(temp_dir.path().join("large"), vec![0u8; 2048]).unwrap();
+        let small = Entry::from_path(temp_dir.path().join("small")).unwrap();
+        let large = Entry::from_path(temp_dir.path().join("large")).unwrap();
+
+        let details = Details::new(vec![Column::Size], TimeStyle::Relative);
+        let rows = details.rows([Some(&small), Some(&large), None], 80, SystemTime::now());
+        assert_eq!(rows, vec!["  1B ", "2.0K ", "     "]);
+    }
+
+    #[test]
+    fn test_rows_drop_columns_that_do_not_fit() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("file"), "a").unwrap();
+        let entry = Entry::from_path(temp_dir.path().join("file")).unwrap();
+        let now = SystemTime::now();
+
+        let details = Details::new(vec![Column::Size, Column::Mtime], TimeStyle::Relative);
+        let wide = details.rows([Some(&entry)], 80, now);
+        assert_eq!(wide, vec!["1B just now "]);
+
+        let narrow = details.rows([Some(&entry)], MIN_NAME_WIDTH + 4, now);
+        assert_eq!(narrow, vec!["1B "]);
+
+        let tiny = details.rows([Some(&entry)], MIN_NAME_WIDTH, now);
+        assert_eq!(tiny, vec![""]);
+    }
+}
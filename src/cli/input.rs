@@ -0,0 +1,69 @@
+//! Candidate paths read from stdin in picker mode.
+//!
+//! When stdin is not a terminal, breeze lists the paths it is given instead of
+//! a directory, like `fzf`. Paths are separated by newlines, or by NUL bytes
+//! with `--read0` so that any file name survives the trip.
+
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+/// Splits everything `reader` yields on `separator`, skipping empty entries.
+pub fn read_paths<R: Read>(mut reader: R, separator: u8) -> io::Result<Vec<PathBuf>> {
+    let mut buffer = Vec::new();
+    reader.read_to_end(&mut buffer)?;
+    Ok(buffer
+        .split(|&byte| byte == separator)
+        .map(|bytes| match separator {
+            b'\n' => bytes.strip_suffix(b"\r").unwrap_or(bytes),
+            _ => bytes,
+        })
+        .filter(|bytes| !bytes.is_empty())
+        .map(path_from_bytes)
+        .collect())
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_newline_separated_paths() {
+        let input = "src/main.rs\nmy notes.txt\r\n\n/tmp/a b\n";
+        let paths = read_paths(input.as_bytes(), b'\n').unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("src/main.rs"),
+                PathBuf::from("my notes.txt"),
+                PathBuf::from("/tmp/a b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_nul_separated_paths() {
+        let input = b"line\nbreak\0plain\0";
+        let paths = read_paths(&input[..], b'\0').unwrap();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("line\nbreak"), PathBuf::from("plain")]
+        );
+    }
+
+    #[test]
+    fn test_read_empty_input() {
+        assert!(read_paths(&b""[..], b'\n').unwrap().is_empty());
+    }
+}
@@ -1 +1,6 @@
 pub mod args;
+pub mod columns;
+pub mod input;
+pub mod output;
+pub mod shell;
+pub mod terminal;
@@ -1 +1,819 @@
-// Output formatting - to be implemented in later phases
+//! The result breeze hands back to the calling shell.
+//!
+//! Every run ends by emitting an [`Output`]: the directory breeze was in, the
+//! [`Command`] the user chose, and the items that command applies to. Shell
+//! wrappers match on the command's wire name, so the vocabulary below is part of
+//! the public protocol and is versioned by [`PROTOCOL_VERSION`].
+//!
+//! | Wire name | Arguments                      | Meaning                                  |
+//! |-----------|--------------------------------|------------------------------------------|
+//! | `no-op`   | none                           | breeze was stopped by SIGTERM or SIGHUP  |
+//! | `quit`    | none                           | the user cancelled                       |
+//! | `select`  | one or more paths              | print or consume the selected paths      |
+//! | `cd`      | exactly one directory          | change the shell's directory             |
+//! | `open`    | one or more paths              | open with the system default handler     |
+//! | `edit`    | one or more paths              | open in `$EDITOR`                        |
+//! | `<name>`  | zero or more paths             | user-defined command                     |
+//!
+//! User-defined commands cannot reuse a built-in wire name, so every command
+//! reads back as the variant that wrote it.
+//!
+//! The fields are written in the order `cwd`, `command`, `items...` using one of
+//! the [`OutputFormat`] encodings. Every encoding except `plain` writes paths as
+//! raw bytes, so names that are not valid UTF-8 survive the trip to the shell.
+//! The `json` encoding additionally carries the active filter and per-item
+//! metadata; JSON strings must be UTF-8, so paths are converted lossily there.
+//!
+//! The process exit status summarises the result as an [`Outcome`], so callers
+//! can branch without parsing the output at all.
+
+use crate::fs::ObjectType;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::str::FromStr;
+
+/// Version of the command vocabulary. Bumped whenever a wire name is removed or
+/// an existing command changes its argument shape.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// How many items a command expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arity {
+    /// The command takes no items.
+    None,
+    /// The command takes exactly one item.
+    One,
+    /// The command takes one or more items.
+    AtLeastOne,
+    /// The command takes any number of items, including none.
+    Any,
+}
+
+impl Arity {
+    pub fn accepts(self, count: usize) -> bool {
+        match self {
+            Arity::None => count == 0,
+            Arity::One => count == 1,
+            Arity::AtLeastOne => count >= 1,
+            Arity::Any => true,
+        }
+    }
+}
+
+/// A command returned to the shell wrapper. Build user-defined commands with
+/// [`Command::custom`], which keeps them apart from the built-ins.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Command {
+    #[default]
+    Noop,
+    Quit,
+    Select,
+    Cd,
+    Open,
+    Edit,
+    Custom(String),
+}
+
+/// The commands with a reserved wire name.
+const BUILT_IN: [Command; 6] = [
+    Command::Noop,
+    Command::Quit,
+    Command::Select,
+    Command::Cd,
+    Command::Open,
+    Command::Edit,
+];
+
+impl Command {
+    /// A user-defined command, if `name` is a valid wire name that no
+    /// built-in command uses.
+    pub fn custom(name: &str) -> Result<Self, ParseCommandError> {
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(ParseCommandError::Invalid);
+        }
+        if BUILT_IN.iter().any(|command| command.name() == name) {
+            return Err(ParseCommandError::Reserved(name.to_string()));
+        }
+        Ok(Command::Custom(name.to_string()))
+    }
+
+    /// The stable name written to the output stream.
+    pub fn name(&self) -> &str {
+        match self {
+            Command::Noop => "no-op",
+            Command::Quit => "quit",
+            Command::Select => "select",
+            Command::Cd => "cd",
+            Command::Open => "open",
+            Command::Edit => "edit",
+            Command::Custom(name) => name,
+        }
+    }
+
+    /// The number of items the command expects.
+    pub fn arity(&self) -> Arity {
+        match self {
+            Command::Noop | Command::Quit => Arity::None,
+            Command::Cd => Arity::One,
+            Command::Select | Command::Open | Command::Edit => Arity::AtLeastOne,
+            Command::Custom(_) => Arity::Any,
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseCommandError {
+    /// The name is empty or contains whitespace.
+    Invalid,
+    /// A user-defined command tried to take a built-in name.
+    Reserved(String),
+}
+
+impl fmt::Display for ParseCommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseCommandError::Invalid => {
+                f.write_str("command name must not be empty or contain whitespace")
+            }
+            ParseCommandError::Reserved(name) => {
+                write!(f, "`{name}` is a built-in command name")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseCommandError {}
+
+impl FromStr for Command {
+    type Err = ParseCommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match BUILT_IN.iter().find(|command| command.name() == s) {
+            Some(command) => Ok(command.clone()),
+            None => Command::custom(s),
+        }
+    }
+}
+
+/// How a run ended, reported as the process exit status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// A command was chosen for one or more items. Exit status 0.
+    Selected,
+    /// The user quit without choosing anything. Exit status 1.
+    Cancelled,
+    /// Only the current directory changed (`cd`, or `no-op` after moving).
+    /// Exit status 3.
+    ChangedDirectory,
+    /// Something went wrong, see stderr. Exit status 4, so it is not
+    /// confused with the status 2 of a command-line usage error.
+    Error,
+}
+
+impl Outcome {
+    pub fn code(self) -> u8 {
+        match self {
+            Outcome::Selected => 0,
+            Outcome::Cancelled => 1,
+            Outcome::ChangedDirectory => 3,
+            Outcome::Error => 4,
+        }
+    }
+}
+
+impl From<Outcome> for ExitCode {
+    fn from(outcome: Outcome) -> Self {
+        ExitCode::from(outcome.code())
+    }
+}
+
+/// How an [`Output`] is encoded on the output stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Space-separated on one line. Human readable, but ambiguous for paths
+    /// containing whitespace.
+    #[default]
+    Plain,
+    /// Every field terminated by a NUL byte.
+    Null,
+    /// Every field POSIX-shell-quoted, space-separated on one line. Suitable
+    /// for `eval "set -- $(breeze --output shell)"`.
+    Shell,
+    /// Every field terminated by a newline, with `\` and newline escaped as
+    /// `\\` and `\n`.
+    Lines,
+    /// A single JSON object on one line, including the active filter and the
+    /// kind of every item.
+    Json,
+}
+
+/// A path the command applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    pub path: PathBuf,
+    pub kind: ObjectType,
+}
+
+impl Item {
+    pub fn new(path: impl Into<PathBuf>, kind: ObjectType) -> Self {
+        Item {
+            path: path.into(),
+            kind,
+        }
+    }
+}
+
+pub struct Output {
+    pub cwd: PathBuf,
+    pub command: Command,
+    pub items: Vec<Item>,
+    pub filter: String,
+    /// The directory the run started in, to tell whether `cwd` moved.
+    start: PathBuf,
+}
+
+impl Output {
+    pub fn new(cwd: impl Into<PathBuf>) -> Self {
+        let cwd = cwd.into();
+        Output {
+            start: cwd.clone(),
+            cwd,
+            command: Command::Noop,
+            items: Vec::new(),
+            filter: String::new(),
+        }
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        match self.command {
+            Command::Quit => Outcome::Cancelled,
+            Command::Noop if self.cwd == self.start => Outcome::Cancelled,
+            Command::Noop | Command::Cd => Outcome::ChangedDirectory,
+            _ => Outcome::Selected,
+        }
+    }
+
+    /// Writes the final directory as raw bytes with no terminator, like
+    /// ranger's `--choosedir`.
+    pub fn write_cwd<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&os_bytes(self.cwd.as_os_str()))
+    }
+
+    /// Writes each item path followed by a NUL byte for [`OutputFormat::Null`]
+    /// and by a newline otherwise, like ranger's `--choosefiles`. Newline
+    /// separated paths are escaped as in [`OutputFormat::Lines`], so a name
+    /// containing a newline still reads back as one path.
+    pub fn write_selection<W: Write>(
+        &self,
+        writer: &mut W,
+        format: OutputFormat,
+    ) -> io::Result<()> {
+        for item in &self.items {
+            let bytes = os_bytes(item.path.as_os_str());
+            if format == OutputFormat::Null {
+                writer.write_all(&bytes)?;
+                writer.write_all(b"\0")?;
+            } else {
+                writer.write_all(&escape_line(&bytes))?;
+                writer.write_all(b"\n")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the output to `writer` using the given encoding.
+    pub fn write<W: Write>(&self, writer: &mut W, format: OutputFormat) -> io::Result<()> {
+        match format {
+            OutputFormat::Plain => writeln!(writer, "{}", self),
+            OutputFormat::Json => {
+                serde_json::to_writer(&mut *writer, &JsonOutput::from(self))?;
+                writer.write_all(b"\n")
+            }
+            OutputFormat::Null => self.write_fields(writer, |writer, _, bytes| {
+                writer.write_all(bytes)?;
+                writer.write_all(b"\0")
+            }),
+            OutputFormat::Lines => self.write_fields(writer, |writer, _, bytes| {
+                writer.write_all(&escape_line(bytes))?;
+                writer.write_all(b"\n")
+            }),
+            OutputFormat::Shell => {
+                self.write_fields(writer, |writer, i, bytes| {
+                    if i > 0 {
+                        writer.write_all(b" ")?;
+                    }
+                    writer.write_all(&shell_quote(bytes))
+                })?;
+                writer.write_all(b"\n")
+            }
+        }
+    }
+
+    /// Passes the fields `cwd`, `command`, `items...` to `write_field` in
+    /// order, with their index and raw bytes.
+    fn write_fields<W: Write>(
+        &self,
+        writer: &mut W,
+        mut write_field: impl FnMut(&mut W, usize, &[u8]) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let fields = std::iter::once(self.cwd.as_os_str())
+            .chain(std::iter::once(OsStr::new(self.command.name())))
+            .chain(self.items.iter().map(|item| item.path.as_os_str()));
+        for (i, field) in fields.enumerate() {
+            write_field(writer, i, &os_bytes(field))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Output {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let items: Vec<Cow<str>> = self
+            .items
+            .iter()
+            .map(|i| i.path.to_string_lossy())
+            .collect();
+        write!(
+            f,
+            "{} {} {}",
+            self.cwd.to_string_lossy(),
+            self.command,
+            items.join(" ")
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    version: u32,
+    cwd: Cow<'a, str>,
+    command: &'a str,
+    filter: &'a str,
+    items: Vec<JsonItem<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonItem<'a> {
+    name: Option<Cow<'a, str>>,
+    path: Cow<'a, str>,
+    kind: &'static str,
+}
+
+impl<'a> From<&'a Output> for JsonOutput<'a> {
+    fn from(output: &'a Output) -> Self {
+        JsonOutput {
+            version: PROTOCOL_VERSION,
+            cwd: output.cwd.to_string_lossy(),
+            command: output.command.name(),
+            filter: &output.filter,
+            items: output
+                .items
+                .iter()
+                .map(|item| JsonItem {
+                    name: item.path.file_name().map(|n| n.to_string_lossy()),
+                    path: item.path.to_string_lossy(),
+                    kind: item.kind.name(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn os_bytes(s: &OsStr) -> Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    Cow::Borrowed(s.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn os_bytes(s: &OsStr) -> Cow<'_, [u8]> {
+    match s.to_string_lossy() {
+        Cow::Borrowed(s) => Cow::Borrowed(s.as_bytes()),
+        Cow::Owned(s) => Cow::Owned(s.into_bytes()),
+    }
+}
+
+fn escape_line(bytes: &[u8]) -> Cow<'_, [u8]> {
+    if !bytes.iter().any(|&b| b == b'\\' || b == b'\n') {
+        return Cow::Borrowed(bytes);
+    }
+    let mut escaped = Vec::with_capacity(bytes.len() + 2);
+    for &b in bytes {
+        match b {
+            b'\\' => escaped.extend_from_slice(b"\\\\"),
+            b'\n' => escaped.extend_from_slice(b"\\n"),
+            _ => escaped.push(b),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+fn shell_quote(bytes: &[u8]) -> Vec<u8> {
+    let mut quoted = Vec::with_capacity(bytes.len() + 2);
+    quoted.push(b'\'');
+    for &b in bytes {
+        if b == b'\'' {
+            quoted.extend_from_slice(b"'\\''");
+        } else {
+            quoted.push(b);
+        }
+    }
+    quoted.push(b'\'');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(output: &Output, format: OutputFormat) -> Vec<u8> {
+        let mut buf = Vec::new();
+        output.write(&mut buf, format).unwrap();
+        buf
+    }
+
+    fn sample_output(items: Vec<PathBuf>) -> Output {
+        let mut output = Output::new("/test/path with spaces");
+        output.command = Command::Select;
+        output.items = items
+            .into_iter()
+            .map(|path| Item::new(path, ObjectType::File))
+            .collect();
+        output
+    }
+
+    #[cfg(unix)]
+    fn tricky_paths() -> Vec<PathBuf> {
+        use std::ffi::OsString;
+        use std::os::unix::ffi::OsStringExt;
+        vec![
+            PathBuf::from("/tmp/with space"),
+            PathBuf::from("/tmp/with\nnewline"),
+            PathBuf::from("/tmp/it's \"quoted\""),
+            PathBuf::from("/tmp/back\\slash\\n"),
+            PathBuf::from(OsString::from_vec(b"/tmp/latin1-\xe9".to_vec())),
+        ]
+    }
+
+    #[cfg(unix)]
+    fn to_path(bytes: &[u8]) -> PathBuf {
+        use std::os::unix::ffi::OsStrExt;
+        PathBuf::from(OsStr::from_bytes(bytes))
+    }
+
+    #[cfg(unix)]
+    fn decode_null(bytes: &[u8]) -> Vec<PathBuf> {
+        let fields = bytes.strip_suffix(b"\0").unwrap();
+        fields.split(|&b| b == 0).map(to_path).collect()
+    }
+
+    #[cfg(unix)]
+    fn decode_lines(bytes: &[u8]) -> Vec<PathBuf> {
+        let mut fields = Vec::new();
+        let mut field = Vec::new();
+        let mut iter = bytes.iter();
+        while let Some(&b) = iter.next() {
+            match b {
+                b'\\' => match iter.next() {
+                    Some(b'n') => field.push(b'\n'),
+                    Some(&other) => field.push(other),
+                    None => panic!("dangling escape"),
+                },
+                b'\n' => fields.push(to_path(&std::mem::take(&mut field))),
+                _ => field.push(b),
+            }
+        }
+        assert!(field.is_empty());
+        fields
+    }
+
+    #[cfg(unix)]
+    fn decode_shell(bytes: &[u8]) -> Vec<PathBuf> {
+        let line = bytes.strip_suffix(b"\n").unwrap();
+        let mut fields = Vec::new();
+        let mut field = Vec::new();
+        let mut quoted = false;
+        let mut iter = line.iter().peekable();
+        while let Some(&b) = iter.next() {
+            match (b, quoted) {
+                (b'\'', _) => quoted = !quoted,
+                (b'\\', false) => field.push(*iter.next().unwrap()),
+                (b' ', false) => fields.push(to_path(&std::mem::take(&mut field))),
+                _ => field.push(b),
+            }
+        }
+        fields.push(to_path(&field));
+        fields
+    }
+
+    #[cfg(unix)]
+    fn expected_fields(output: &Output) -> Vec<PathBuf> {
+        let mut fields = vec![output.cwd.clone(), PathBuf::from(output.command.name())];
+        fields.extend(output.items.iter().map(|item| item.path.clone()));
+        fields
+    }
+
+    #[test]
+    fn test_command_wire_names_round_trip() {
+        let commands = [
+            Command::Noop,
+            Command::Quit,
+            Command::Select,
+            Command::Cd,
+            Command::Open,
+            Command::Edit,
+            Command::custom("preview").unwrap(),
+        ];
+        for command in commands {
+            assert_eq!(command.name().parse::<Command>(), Ok(command));
+        }
+    }
+
+    #[test]
+    fn test_command_parse_rejects_invalid_names() {
+        assert_eq!("".parse::<Command>(), Err(ParseCommandError::Invalid));
+        assert_eq!(
+            "two words".parse::<Command>(),
+            Err(ParseCommandError::Invalid)
+        );
+    }
+
+    #[test]
+    fn test_custom_command_rejects_built_in_names() {
+        for command in BUILT_IN {
+            assert_eq!(
+                Command::custom(command.name()),
+                Err(ParseCommandError::Reserved(command.name().to_string()))
+            );
+        }
+        assert_eq!(Command::custom(""), Err(ParseCommandError::Invalid));
+    }
+
+    #[test]
+    fn test_command_arity() {
+        assert!(Command::Noop.arity().accepts(0));
+        assert!(!Command::Quit.arity().accepts(1));
+        assert!(Command::Cd.arity().accepts(1));
+        assert!(!Command::Cd.arity().accepts(2));
+        assert!(!Command::Select.arity().accepts(0));
+        assert!(Command::Edit.arity().accepts(3));
+        assert!(Command::Custom("x".to_string()).arity().accepts(0));
+    }
+
+    #[test]
+    fn test_output_outcome() {
+        let mut output = Output::new("/test/path");
+        assert_eq!(output.outcome(), Outcome::Cancelled);
+
+        output.cwd = PathBuf::from("/test");
+        assert_eq!(output.outcome(), Outcome::ChangedDirectory);
+
+        output.command = Command::Cd;
+        assert_eq!(output.outcome(), Outcome::ChangedDirectory);
+
+        output.command = Command::Quit;
+        assert_eq!(output.outcome(), Outcome::Cancelled);
+
+        for command in [Command::Select, Command::Edit, Command::Custom("x".into())] {
+            output.command = command;
+            assert_eq!(output.outcome(), Outcome::Selected);
+        }
+    }
+
+    #[test]
+    fn test_outcome_codes_match_readme() {
+        // Status 2 is left to clap for usage errors.
+        let codes = [
+            Outcome::Selected.code(),
+            Outcome::Cancelled.code(),
+            Outcome::ChangedDirectory.code(),
+            Outcome::Error.code(),
+        ];
+        assert_eq!(codes, [0, 1, 3, 4]);
+    }
+
+    #[test]
+    fn test_output_new() {
+        let output = Output::new("/test/path".to_string());
+        assert_eq!(output.cwd, PathBuf::from("/test/path"));
+        assert_eq!(output.command, Command::Noop);
+        assert_eq!(output.items.len(), 0);
+    }
+
+    #[test]
+    fn test_output_display_no_items() {
+        let output = Output::new("/test/path".to_string());
+        assert_eq!(format!("{}", output), "/test/path no-op ");
+    }
+
+    #[test]
+    fn test_output_display_with_single_item() {
+        let mut output = Output::new("/test/path".to_string());
+        output.command = Command::Select;
+        output.items = vec![Item::new("/test/path/file.txt", ObjectType::File)];
+        assert_eq!(
+            format!("{}", output),
+            "/test/path select /test/path/file.txt"
+        );
+    }
+
+    #[test]
+    fn test_output_display_with_multiple_items() {
+        let mut output = Output::new("/test/path".to_string());
+        output.command = Command::Select;
+        output.items = vec![
+            Item::new("/test/path/file1.txt", ObjectType::File),
+            Item::new("/test/path/file2.txt", ObjectType::File),
+            Item::new("/test/path/file3.txt", ObjectType::File),
+        ];
+        assert_eq!(
+            format!("{}", output),
+            "/test/path select /test/path/file1.txt /test/path/file2.txt /test/path/file3.txt"
+        );
+    }
+
+    #[test]
+    fn test_output_display_with_spaces_in_paths() {
+        let mut output = Output::new("/test/path with spaces".to_string());
+        output.command = Command::Select;
+        output.items = vec![Item::new(
+            "/test/path with spaces/file with spaces.txt",
+            ObjectType::File,
+        )];
+        assert_eq!(
+            format!("{}", output),
+            "/test/path with spaces select /test/path with spaces/file with spaces.txt"
+        );
+    }
+
+    #[test]
+    fn test_output_write_plain_matches_display() {
+        let output = sample_output(vec![PathBuf::from("/test/path with spaces/a b.txt")]);
+        assert_eq!(
+            encode(&output, OutputFormat::Plain),
+            format!("{}\n", output).into_bytes()
+        );
+    }
+
+    #[test]
+    fn test_output_write_null() {
+        let output = sample_output(vec![PathBuf::from("/test/path with spaces/a b.txt")]);
+        assert_eq!(
+            encode(&output, OutputFormat::Null),
+            b"/test/path with spaces\0select\0/test/path with spaces/a b.txt\0"
+        );
+    }
+
+    #[test]
+    fn test_output_write_null_no_items() {
+        let output = Output::new("/test/path");
+        assert_eq!(encode(&output, OutputFormat::Null), b"/test/path\0no-op\0");
+    }
+
+    #[test]
+    fn test_output_write_shell() {
+        let output = sample_output(vec![PathBuf::from("/tmp/it's")]);
+        assert_eq!(
+            encode(&output, OutputFormat::Shell),
+            b"'/test/path with spaces' 'select' '/tmp/it'\\''s'\n"
+        );
+    }
+
+    #[test]
+    fn test_output_write_lines_escapes_newlines_and_backslashes() {
+        let output = sample_output(vec![PathBuf::from("/tmp/a\nb\\c")]);
+        assert_eq!(
+            encode(&output, OutputFormat::Lines),
+            b"/test/path with spaces\nselect\n/tmp/a\\nb\\\\c\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_null_round_trips_arbitrary_paths() {
+        let output = sample_output(tricky_paths());
+        let decoded = decode_null(&encode(&output, OutputFormat::Null));
+        assert_eq!(decoded, expected_fields(&output));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_lines_round_trips_arbitrary_paths() {
+        let output = sample_output(tricky_paths());
+        let decoded = decode_lines(&encode(&output, OutputFormat::Lines));
+        assert_eq!(decoded, expected_fields(&output));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_shell_round_trips_arbitrary_paths() {
+        let output = sample_output(tricky_paths());
+        let decoded = decode_shell(&encode(&output, OutputFormat::Shell));
+        assert_eq!(decoded, expected_fields(&output));
+    }
+
+    #[test]
+    fn test_output_write_json() {
+        let mut output = Output::new("/test/path");
+        output.command = Command::Edit;
+        output.filter = "fi".to_string();
+        output.items = vec![
+            Item::new("/test/path/file.txt", ObjectType::File),
+            Item::new("/test/path/dir", ObjectType::Directory),
+        ];
+
+        let encoded = encode(&output, OutputFormat::Json);
+        assert_eq!(encoded.last(), Some(&b'\n'));
+        let value: serde_json::Value = serde_json::from_slice(&encoded).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "version": PROTOCOL_VERSION,
+                "cwd": "/test/path",
+                "command": "edit",
+                "filter": "fi",
+                "items": [
+                    {"name": "file.txt", "path": "/test/path/file.txt", "kind": "file"},
+                    {"name": "dir", "path": "/test/path/dir", "kind": "directory"},
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_output_write_json_no_items() {
+        let output = Output::new("/test/path");
+        let value: serde_json::Value =
+            serde_json::from_slice(&encode(&output, OutputFormat::Json)).unwrap();
+        assert_eq!(value["command"], "no-op");
+        assert_eq!(value["filter"], "");
+        assert_eq!(value["items"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_output_write_cwd() {
+        let output = sample_output(vec![PathBuf::from("/tmp/a")]);
+        let mut buf = Vec::new();
+        output.write_cwd(&mut buf).unwrap();
+        assert_eq!(buf, b"/test/path with spaces");
+    }
+
+    #[test]
+    fn test_output_write_selection() {
+        let output = sample_output(vec![PathBuf::from("/tmp/a b"), PathBuf::from("/tmp/c")]);
+
+        let mut lines = Vec::new();
+        output
+            .write_selection(&mut lines, OutputFormat::Plain)
+            .unwrap();
+        assert_eq!(lines, b"/tmp/a b\n/tmp/c\n");
+
+        let mut nul = Vec::new();
+        output
+            .write_selection(&mut nul, OutputFormat::Null)
+            .unwrap();
+        assert_eq!(nul, b"/tmp/a b\0/tmp/c\0");
+    }
+
+    #[test]
+    fn test_output_write_selection_escapes_newlines() {
+        let output = sample_output(vec![
+            PathBuf::from("/tmp/two\nlines"),
+            PathBuf::from("/tmp/a\\b"),
+        ]);
+
+        let mut lines = Vec::new();
+        output
+            .write_selection(&mut lines, OutputFormat::Lines)
+            .unwrap();
+        assert_eq!(lines, b"/tmp/two\\nlines\n/tmp/a\\\\b\n");
+
+        let mut nul = Vec::new();
+        output
+            .write_selection(&mut nul, OutputFormat::Null)
+            .unwrap();
+        assert_eq!(nul, b"/tmp/two\nlines\0/tmp/a\\b\0");
+    }
+
+    #[test]
+    fn test_output_write_selection_no_items() {
+        let output = Output::new("/test/path");
+        let mut buf = Vec::new();
+        output
+            .write_selection(&mut buf, OutputFormat::Plain)
+            .unwrap();
+        assert!(buf.is_empty());
+    }
+}
@@ -0,0 +1,332 @@
+//! Shell integration scripts printed by `breeze init <shell>`.
+//!
+//! breeze runs as a child process and cannot change the directory of the shell
+//! that started it. The generated wrapper function runs breeze, reads the
+//! NUL-separated [`Output`](crate::cli::output::Output) and then changes
+//! directory or runs the returned command in the calling shell.
+//!
+//! Commands that hand control back (`edit`, `open` and custom commands) start
+//! a session loop: breeze is reopened with `--resume` on a temporary session
+//! file, so it comes back in the same directory with the same filter and
+//! selection. `cd`, `select`, `quit` and `no-op` end the loop. The wrapper
+//! returns 0 once it has applied the result (breeze's 0 or 3), and passes any
+//! other exit status of the last breeze run through, such as 1 for a cancel or
+//! 4 for an error.
+
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+const BASH: &str = r#"# breeze shell integration for bash
+# Add to ~/.bashrc:  eval "$(breeze-cli init bash)"
+
+__breeze_open() {
+    if command -v xdg-open >/dev/null 2>&1; then
+        xdg-open "$1"
+    else
+        open "$1"
+    fi
+}
+
+__breeze_run() {
+    local __breeze_cmd="$1"
+    shift
+    case "$__breeze_cmd" in
+        cd) builtin cd -- "$1" ;;
+        select) printf '%s\n' "$@" ;;
+        edit) ${VISUAL:-${EDITOR:-vi}} "$@" ;;
+        open) for __breeze_item in "$@"; do __breeze_open "$__breeze_item"; done ;;
+        quit | no-op) ;;
+        *) "$__breeze_cmd" "$@" ;;
+    esac
+}
+
+__BREEZE_CMD__() {
+    local __breeze_session __breeze_out __breeze_field __breeze_status=0
+    local -a __breeze_fields
+    __breeze_session="$(mktemp "${TMPDIR:-/tmp}/breeze-session.XXXXXX")" || return
+    __breeze_out="$(mktemp "${TMPDIR:-/tmp}/breeze-out.XXXXXX")" || {
+        rm -f -- "$__breeze_session"
+        return 1
+    }
+    while :; do
+        command "${BREEZE_BIN:-breeze-cli}" --output null --resume "$__breeze_session" "$@" >"$__breeze_out"
+        __breeze_status=$?
+        __breeze_fields=()
+        while IFS= read -r -d '' __breeze_field; do
+            __breeze_fields+=("$__breeze_field")
+        done <"$__breeze_out"
+        [ "${#__breeze_fields[@]}" -ge 2 ] || break
+        __breeze_run "${__breeze_fields[@]:1}"
+        case "${__breeze_fields[1]}" in
+            cd | select | quit | no-op) break ;;
+        esac
+    done
+    rm -f -- "$__breeze_session" "$__breeze_out"
+    # 3 only means the directory changed, which the wrapper has just applied.
+    [ "$__breeze_status" -eq 3 ] && return 0
+    return "$__breeze_status"
+}
+"#;
+
+const ZSH: &str = r#"# breeze shell integration for zsh
+# Add to ~/.zshrc:  eval "$(breeze-cli init zsh)"
+
+__breeze_open() {
+    if (( $+commands[xdg-open] )); then
+        xdg-open "$1"
+    else
+        open "$1"
+    fi
+}
+
+__breeze_run() {
+    local __breeze_cmd="$1"
+    shift
+    case "$__breeze_cmd" in
+        cd) builtin cd -- "$1" ;;
+        select) print -rl -- "$@" ;;
+        edit) ${=${VISUAL:-${EDITOR:-vi}}} "$@" ;;
+        open) for __breeze_item in "$@"; do __breeze_open "$__breeze_item"; done ;;
+        quit | no-op) ;;
+        *) "$__breeze_cmd" "$@" ;;
+    esac
+}
+
+__BREEZE_CMD__() {
+    local __breeze_session __breeze_out __breeze_status=0
+    local -a __breeze_fields
+    __breeze_session="$(mktemp "${TMPDIR:-/tmp}/breeze-session.XXXXXX")" || return
+    while :; do
+        __breeze_out="$(command "${BREEZE_BIN:-breeze-cli}" --output null --resume "$__breeze_session" "$@")"
+        __breeze_status=$?
+        __breeze_fields=("${(@0)__breeze_out}")
+        (( ${#__breeze_fields} >= 3 )) || break
+        __breeze_run "${(@)__breeze_fields[2,-2]}"
+        case "${__breeze_fields[2]}" in
+            cd | select | quit | no-op) break ;;
+        esac
+    done
+    rm -f -- "$__breeze_session"
+    # 3 only means the directory changed, which the wrapper has just applied.
+    [ "$__breeze_status" -eq 3 ] && return 0
+    return "$__breeze_status"
+}
+"#;
+
+const FISH: &str = r#"# breeze shell integration for fish
+# Add to ~/.config/fish/config.fish:  breeze-cli init fish | source
+
+function __breeze_open
+    if command -q xdg-open
+        xdg-open $argv[1]
+    else
+        open $argv[1]
+    end
+end
+
+function __breeze_run
+    set -l cmd $argv[1]
+    set -l items $argv[2..-1]
+    switch $cmd
+        case cd
+            builtin cd -- $items[1]
+        case select
+            printf '%s\n' $items
+        case edit
+            set -l editor vi
+            set -q EDITOR; and set editor (string split ' ' -- $EDITOR)
+            set -q VISUAL; and set editor (string split ' ' -- $VISUAL)
+            $editor $items
+        case open
+            for item in $items
+                __breeze_open $item
+            end
+        case quit no-op
+        case '*'
+            $cmd $items
+    end
+end
+
+function __BREEZE_CMD__
+    set -l bin breeze-cli
+    set -q BREEZE_BIN; and set bin $BREEZE_BIN
+    set -l tmp /tmp
+    set -q TMPDIR; and set tmp $TMPDIR
+    set -l session (mktemp $tmp/breeze-session.XXXXXX)
+    or return
+    set -l out (mktemp $tmp/breeze-out.XXXXXX)
+    or begin
+        rm -f -- $session
+        return 1
+    end
+    set -l breeze_status 0
+    while true
+        command $bin --output null --resume $session $argv >$out
+        set breeze_status $status
+        set -l fields (string split0 <$out)
+        test (count $fields) -ge 2; or break
+        __breeze_run $fields[2..-1]
+        contains -- $fields[2] cd select quit no-op; and break
+    end
+    rm -f -- $session $out
+    # 3 only means the directory changed, which the wrapper has just applied.
+    test $breeze_status -eq 3; and return 0
+    return $breeze_status
+end
+"#;
+
+/// Returns the integration script for `shell`, defining a wrapper function
+/// called `cmd`.
+pub fn init_script(shell: Shell, cmd: &str) -> String {
+    let template = match shell {
+        Shell::Bash => BASH,
+        Shell::Zsh => ZSH,
+        Shell::Fish => FISH,
+    };
+    template.replace("__BREEZE_CMD__", cmd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn has_bash() -> bool {
+        Command::new("bash").arg("-c").arg("true").status().is_ok()
+    }
+
+    /// Runs `script` in bash after sourcing the bash integration, with
+    /// `BREEZE_BIN` pointing at a stub shell script with the given body.
+    fn run_bash_wrapper(stub_body: &str, script: &str) -> String {
+        let temp_dir = TempDir::new().unwrap();
+        let stub = temp_dir.path().join("fake-breeze");
+        fs::write(&stub, format!("#!/bin/sh\n{}\n", stub_body)).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&stub, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let output = Command::new("bash")
+            .arg("-c")
+            .arg(format!("{}\n{}", init_script(Shell::Bash, "bz"), script))
+            .env("BREEZE_BIN", &stub)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[test]
+    fn test_init_script_uses_wrapper_name() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = init_script(shell, "bzz");
+            assert!(!script.contains("__BREEZE_CMD__"));
+            assert!(script.contains("bzz"));
+        }
+    }
+
+    #[test]
+    fn test_init_script_requests_null_output() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            assert!(init_script(shell, "bz").contains("--output null"));
+        }
+    }
+
+    #[test]
+    fn test_init_script_handles_every_builtin_command() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = init_script(shell, "bz");
+            for name in ["cd", "select", "edit", "open", "quit", "no-op"] {
+                assert!(script.contains(name), "{shell:?} is missing {name}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_bash_script_is_valid_syntax() {
+        if !has_bash() {
+            return;
+        }
+        let status = Command::new("bash")
+            .arg("-n")
+            .arg("-c")
+            .arg(init_script(Shell::Bash, "bz"))
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_bash_wrapper_changes_directory() {
+        if !has_bash() {
+            return;
+        }
+        let target = TempDir::new().unwrap();
+        let target = target.path().join("dir with space");
+        fs::create_dir(&target).unwrap();
+        let stub = format!("printf '/start\\0cd\\0{}\\0'; exit 3", target.display());
+
+        let stdout = run_bash_wrapper(&stub, "bz && pwd");
+        assert_eq!(stdout.trim_end(), target.to_string_lossy());
+    }
+
+    #[test]
+    fn test_bash_wrapper_prints_selection() {
+        if !has_bash() {
+            return;
+        }
+        let stdout = run_bash_wrapper(r"printf '/start\0select\0/a b\0/c\0'", "bz");
+        assert_eq!(stdout, "/a b\n/c\n");
+    }
+
+    #[test]
+    fn test_bash_wrapper_passes_session_file() {
+        if !has_bash() {
+            return;
+        }
+        let stub = r#"printf '/start\0select\0%s\0%s\0' "$3" "$4""#;
+        let stdout = run_bash_wrapper(stub, "bz");
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines[0], "--resume");
+        assert!(lines[1].contains("breeze-session."));
+    }
+
+    #[test]
+    fn test_bash_wrapper_loops_until_terminal_command() {
+        if !has_bash() {
+            return;
+        }
+        // Returns a custom command on the first run and `quit` once the
+        // session file has been written, which ends the loop.
+        let stub = r#"if [ -s "$4" ]; then
+    printf '/start\0quit\0'
+else
+    echo '{}' > "$4"
+    printf '/start\0echo\0one\0two\0'
+fi"#;
+        let stdout = run_bash_wrapper(stub, "bz; echo done");
+        assert_eq!(stdout, "one two\ndone\n");
+    }
+
+    #[test]
+    fn test_bash_wrapper_returns_breeze_exit_status() {
+        if !has_bash() {
+            return;
+        }
+        let stdout = run_bash_wrapper(r"printf '/start\0quit\0'; exit 1", "bz; echo $?");
+        assert_eq!(stdout, "1\n");
+        let stdout = run_bash_wrapper(r"printf '/start\0select\0/a\0'; exit 0", "bz; echo $?");
+        assert_eq!(stdout, "/a\n0\n");
+        let stdout = run_bash_wrapper(r"exit 4", "bz; echo $?");
+        assert_eq!(stdout, "4\n");
+    }
+}
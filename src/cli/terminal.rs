@@ -0,0 +1,265 @@
+//! Terminal setup and teardown.
+//!
+//! The TUI draws on stderr in raw mode, either on the alternate screen or, with
+//! [`Screen::Inline`], in a few rows below the prompt. Both are undone when the
+//! [`TerminalGuard`] is dropped, so an early return leaves the shell usable,
+//! and the panic hook does the same before the panic message prints.
+//! [`Signals`] lets the event loop exit on SIGTERM/SIGHUP and handle job
+//! control itself, since the terminal has to be handed back before stopping.
+
+use crossterm::{
+    ExecutableCommand,
+    cursor::Show,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use std::fmt;
+use std::io::{self, Stderr, stderr};
+use std::panic;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Longest the event loop waits for input before checking for signals.
+pub const SIGNAL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Smallest inline viewport that still fits the header, a row and the footer.
+pub const MIN_INLINE_HEIGHT: u16 = 5;
+
+/// Inline height used by `--inline` without `--height`.
+pub const DEFAULT_INLINE_HEIGHT: Height = Height::Percent(40);
+
+/// Where the TUI is drawn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Screen {
+    /// The whole terminal, on the alternate screen.
+    #[default]
+    Fullscreen,
+    /// Below the prompt, keeping the scrollback visible.
+    Inline(Height),
+}
+
+/// Height of an inline viewport, as `N` rows or `N%` of the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Height {
+    Rows(u16),
+    Percent(u16),
+}
+
+impl Height {
+    /// Rows to use on a terminal `terminal_rows` high.
+    pub fn rows(self, terminal_rows: u16) -> u16 {
+        let rows = match self {
+            Height::Rows(rows) => rows,
+            Height::Percent(percent) => {
+                (u32::from(terminal_rows) * u32::from(percent) / 100) as u16
+            }
+        };
+        rows.max(MIN_INLINE_HEIGHT).min(terminal_rows)
+    }
+}
+
+impl FromStr for Height {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("expected N or N% with N > 0, got {:?}", s);
+        let (digits, percent) = match s.strip_suffix('%') {
+            Some(digits) => (digits, true),
+            None => (s, false),
+        };
+        let value: u16 = digits.parse().map_err(|_| invalid())?;
+        match (value, percent) {
+            (0, _) => Err(invalid()),
+            (101.., true) => Err(invalid()),
+            (value, true) => Ok(Height::Percent(value)),
+            (value, false) => Ok(Height::Rows(value)),
+        }
+    }
+}
+
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Height::Rows(rows) => write!(f, "{}", rows),
+            Height::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+/// Whether the terminal is currently in raw mode.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// Whether the alternate screen has to be left on restore.
+static ALTERNATE: AtomicBool = AtomicBool::new(false);
+
+/// Keeps the terminal in raw mode, on the alternate screen for
+/// [`Screen::Fullscreen`], while alive.
+pub struct TerminalGuard<'a> {
+    handle: &'a Stderr,
+    screen: Screen,
+}
+
+impl<'a> TerminalGuard<'a> {
+    pub fn enter(handle: &'a Stderr, screen: Screen) -> io::Result<Self> {
+        activate(handle, screen)?;
+        Ok(Self { handle, screen })
+    }
+
+    /// Hands the terminal back to the shell and stops the process, then takes
+    /// the terminal over again once the process is continued (`fg`).
+    pub fn suspend(&mut self) -> io::Result<()> {
+        restore()?;
+        #[cfg(unix)]
+        signal_hook::low_level::emulate_default_handler(signal_hook::consts::SIGTSTP)?;
+        activate(self.handle, self.screen)
+    }
+}
+
+impl Drop for TerminalGuard<'_> {
+    fn drop(&mut self) {
+        let _ = restore();
+    }
+}
+
+fn activate(mut handle: &Stderr, screen: Screen) -> io::Result<()> {
+    enable_raw_mode()?;
+    ACTIVE.store(true, Ordering::SeqCst);
+    if screen == Screen::Fullscreen {
+        handle.execute(EnterAlternateScreen)?;
+        ALTERNATE.store(true, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+/// Leaves raw mode and the alternate screen, if breeze entered them.
+pub fn restore() -> io::Result<()> {
+    if ACTIVE.swap(false, Ordering::SeqCst) {
+        disable_raw_mode()?;
+        if ALTERNATE.swap(false, Ordering::SeqCst) {
+            stderr().execute(LeaveAlternateScreen)?;
+        }
+        stderr().execute(Show)?;
+    }
+    Ok(())
+}
+
+/// Restores the terminal before the default hook prints the panic message,
+/// which would otherwise be lost on the alternate screen.
+pub fn install_panic_hook() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore();
+        hook(info);
+    }));
+}
+
+/// Signals received since the event loop last checked.
+#[derive(Debug, Clone, Default)]
+pub struct Signals {
+    terminate: Arc<AtomicBool>,
+    suspend: Arc<AtomicBool>,
+}
+
+impl Signals {
+    /// Routes SIGTERM and SIGHUP to a clean exit and SIGTSTP to a suspend.
+    pub fn register() -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            Self::routed(|signal, flag| signal_hook::flag::register(signal, flag).map(drop))
+        }
+        #[cfg(not(unix))]
+        Ok(Self::default())
+    }
+
+    /// Hands each flag to `register` along with a signal that should set it.
+    #[cfg(unix)]
+    fn routed(
+        mut register: impl FnMut(i32, Arc<AtomicBool>) -> io::Result<()>,
+    ) -> io::Result<Self> {
+        use signal_hook::consts::{SIGHUP, SIGTERM, SIGTSTP};
+        let signals = Self::default();
+        register(SIGTERM, Arc::clone(&signals.terminate))?;
+        register(SIGHUP, Arc::clone(&signals.terminate))?;
+        register(SIGTSTP, Arc::clone(&signals.suspend))?;
+        Ok(signals)
+    }
+
+    pub fn terminate_requested(&self) -> bool {
+        self.terminate.load(Ordering::SeqCst)
+    }
+
+    /// Asks the event loop to suspend, as SIGTSTP does. Raw mode swallows
+    /// Ctrl-Z, so the key binding goes through here.
+    pub fn request_suspend(&self) {
+        self.suspend.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether a suspend was requested since the last call.
+    pub fn take_suspend(&self) -> bool {
+        self.suspend.swap(false, Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_without_terminal_is_a_no_op() {
+        assert!(restore().is_ok());
+    }
+
+    #[test]
+    fn test_parse_height() {
+        assert_eq!("12".parse(), Ok(Height::Rows(12)));
+        assert_eq!("40%".parse(), Ok(Height::Percent(40)));
+        assert!("0".parse::<Height>().is_err());
+        assert!("150%".parse::<Height>().is_err());
+        assert!("tall".parse::<Height>().is_err());
+        assert_eq!(Height::Percent(40).to_string(), "40%");
+    }
+
+    #[test]
+    fn test_height_rows_are_clamped_to_terminal() {
+        assert_eq!(Height::Rows(12).rows(50), 12);
+        assert_eq!(Height::Rows(80).rows(50), 50);
+        assert_eq!(Height::Rows(1).rows(50), MIN_INLINE_HEIGHT);
+        assert_eq!(Height::Percent(40).rows(50), 20);
+        assert_eq!(Height::Percent(1).rows(50), MIN_INLINE_HEIGHT);
+        assert_eq!(Height::Rows(10).rows(3), 3);
+    }
+
+    #[test]
+    fn test_request_suspend_is_taken_once() {
+        let signals = Signals::default();
+        assert!(!signals.take_suspend());
+        signals.request_suspend();
+        assert!(signals.take_suspend());
+        assert!(!signals.take_suspend());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_signals_set_flags() {
+        use signal_hook::consts::{SIGHUP, SIGTERM, SIGTSTP};
+        use std::collections::HashMap;
+
+        // Deliver signals by hand instead of installing process-wide
+        // handlers, which would reach every test running alongside.
+        let mut flags = HashMap::new();
+        let signals = Signals::routed(|signal, flag| {
+            flags.insert(signal, flag);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(flags.len(), 3);
+
+        flags[&SIGTSTP].store(true, Ordering::SeqCst);
+        assert!(signals.take_suspend());
+        assert!(!signals.terminate_requested());
+
+        flags[&SIGHUP].store(true, Ordering::SeqCst);
+        assert!(signals.terminate_requested());
+        assert!(Arc::ptr_eq(&flags[&SIGTERM], &flags[&SIGHUP]));
+    }
+}
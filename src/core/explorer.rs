@@ -1,33 +1,144 @@
 use crate::error::ExplorerError;
-use crate::fs::list_directory;
-use std::fs::DirEntry;
-use std::path::PathBuf;
+use crate::fs::{Entry, ListOptions, ListingCache, Loader, Stamp, list_directory};
+use std::env;
+use std::path::{Component, Path, PathBuf};
+
+/// How the explorer names the directories it moves through, as with the
+/// shell's `cd -P` and `cd -L`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PathMode {
+    /// Resolve every symlink, so the current directory is always canonical.
+    #[default]
+    Physical,
+    /// Keep the path as navigated: entering a symlinked directory keeps the
+    /// link in the path, and `..` removes the last component lexically.
+    Logical,
+}
 
 #[derive(Debug)]
 pub struct Explorer {
     pub current_dir: PathBuf,
+    pub options: ListOptions,
+    pub path_mode: PathMode,
+    /// Listings of visited directories, reused while they are unchanged.
+    pub cache: ListingCache,
 }
 
 impl Explorer {
     pub fn new(directory: PathBuf) -> Result<Self, ExplorerError> {
+        Self::with_path_mode(directory, PathMode::Physical)
+    }
+
+    pub fn with_path_mode(directory: PathBuf, path_mode: PathMode) -> Result<Self, ExplorerError> {
         if !directory.exists() {
             return Err(ExplorerError::InvalidDirectory(
                 directory.to_string_lossy().to_string(),
             ));
         }
 
+        let base = match path_mode {
+            PathMode::Physical => PathBuf::new(),
+            PathMode::Logical => logical_current_dir()?,
+        };
         Ok(Explorer {
-            current_dir: directory.canonicalize()?,
+            current_dir: resolve(&base, &directory, path_mode)?,
+            options: ListOptions::default(),
+            path_mode,
+            cache: ListingCache::default(),
         })
     }
 
-    pub fn ls(&self) -> Result<Vec<DirEntry>, ExplorerError> {
-        list_directory(&self.current_dir)
+    pub fn with_options(mut self, options: ListOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Caps the memory used for cached listings at `capacity` bytes; 0
+    /// disables the cache.
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache = ListingCache::new(capacity);
+        self
+    }
+
+    pub fn ls(&mut self) -> Result<Vec<Entry>, ExplorerError> {
+        let directory = self.current_dir.clone();
+        self.list(&directory)
+    }
+
+    /// Moves to `directory` and returns its listing. If the directory cannot
+    /// be listed, the explorer stays where it was.
+    pub fn cd(&mut self, directory: PathBuf) -> Result<Vec<Entry>, ExplorerError> {
+        let target = resolve(&self.current_dir, &directory, self.path_mode)?;
+        let entries = self.list(&target)?;
+        self.current_dir = target;
+        Ok(entries)
+    }
+
+    /// Starts listing the current directory in the background.
+    pub fn start_ls(&mut self) -> Loader {
+        self.start_loading(self.current_dir.clone())
+    }
+
+    /// Starts reading the current directory again, even if its cached
+    /// listing still looks current.
+    pub fn start_refresh(&mut self) -> Loader {
+        self.cache.remove(&self.current_dir);
+        self.start_ls()
     }
 
-    pub fn cd(&mut self, directory: PathBuf) -> Result<Vec<DirEntry>, ExplorerError> {
-        self.current_dir = directory.canonicalize()?;
-        self.ls()
+    /// Starts listing `directory` in the background. The explorer only moves
+    /// there on [`enter`](Self::enter), so it can stay put if the listing
+    /// fails or is abandoned.
+    pub fn start_cd(&mut self, directory: PathBuf) -> Result<Loader, ExplorerError> {
+        let target = resolve(&self.current_dir, &directory, self.path_mode)?;
+        Ok(self.start_loading(target))
+    }
+
+    /// Moves to the directory `loader` lists.
+    pub fn enter(&mut self, loader: &Loader) {
+        self.current_dir = loader.path().to_path_buf();
+    }
+
+    /// Caches the listing `loader` read, if it got through the whole
+    /// directory.
+    pub fn remember(&mut self, loader: &Loader) {
+        if let Some(stamp) = loader.stamp()
+            && loader.is_complete()
+        {
+            self.cache.insert(
+                loader.path().to_path_buf(),
+                stamp,
+                loader.options().show_hidden,
+                loader.entries().to_vec(),
+            );
+        }
+    }
+
+    /// The listing of `directory`, from the cache if it is still current.
+    fn list(&mut self, directory: &Path) -> Result<Vec<Entry>, ExplorerError> {
+        let show_hidden = self.options.show_hidden;
+        let stamp = Stamp::of(directory);
+        if let Some(stamp) = stamp
+            && let Some(entries) = self.cache.get(directory, stamp, show_hidden)
+        {
+            let mut entries = entries.to_vec();
+            entries.sort_by(|a, b| self.options.sort.compare(a, b));
+            return Ok(entries);
+        }
+        let entries = list_directory(directory, &self.options)?;
+        if let Some(stamp) = stamp {
+            let path = directory.to_path_buf();
+            self.cache.insert(path, stamp, show_hidden, entries.clone());
+        }
+        Ok(entries)
+    }
+
+    fn start_loading(&mut self, directory: PathBuf) -> Loader {
+        let stamp = Stamp::of(&directory);
+        match stamp.and_then(|stamp| self.cache.get(&directory, stamp, self.options.show_hidden)) {
+            Some(entries) => Loader::cached(directory, self.options, entries.to_vec()),
+            None => Loader::start(directory, self.options).with_stamp(stamp),
+        }
     }
 
     pub fn cwd(&self) -> String {
@@ -35,6 +146,53 @@ impl Explorer {
     }
 }
 
+/// Turns `directory`, relative to `base`, into the path the explorer stores.
+fn resolve(base: &Path, directory: &Path, path_mode: PathMode) -> Result<PathBuf, ExplorerError> {
+    match path_mode {
+        PathMode::Physical => Ok(base.join(directory).canonicalize()?),
+        PathMode::Logical => {
+            let path = normalize_lexically(&base.join(directory));
+            // Fail the same way `canonicalize` would for a missing directory.
+            path.metadata()?;
+            Ok(path)
+        }
+    }
+}
+
+/// The shell's logical working directory: `$PWD` when it still names the
+/// process's working directory, otherwise the physical one.
+fn logical_current_dir() -> Result<PathBuf, ExplorerError> {
+    let physical = env::current_dir()?;
+    if let Some(pwd) = env::var_os("PWD").map(PathBuf::from)
+        && pwd.is_absolute()
+        && pwd.canonicalize().ok() == physical.canonicalize().ok()
+    {
+        return Ok(pwd);
+    }
+    Ok(physical)
+}
+
+/// Removes `.` components and resolves `..` against the preceding component
+/// without looking at the file system.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                // `..` at the root stays at the root.
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => normalized.push(component),
+            },
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -66,14 +224,19 @@ mod tests {
         let relative_path = temp_dir.path().join(".");
         let explorer = Explorer::new(relative_path).unwrap();
         let cwd = explorer.cwd();
-        let expected = temp_dir.path().canonicalize().unwrap().to_string_lossy().to_string();
+        let expected = temp_dir
+            .path()
+            .canonicalize()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
         assert_eq!(cwd, expected);
     }
 
     #[test]
     fn test_ls_empty_directory() {
         let temp_dir = TempDir::new().unwrap();
-        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let mut explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
         let entries = explorer.ls().unwrap();
         assert_eq!(entries.len(), 0);
     }
@@ -85,13 +248,13 @@ mod tests {
         fs::write(temp_dir.path().join("file1.txt"), "content").unwrap();
         fs::write(temp_dir.path().join("file2.txt"), "content").unwrap();
 
-        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let mut explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
         let entries = explorer.ls().unwrap();
         assert_eq!(entries.len(), 3);
 
         let names: Vec<String> = entries
             .iter()
-            .map(|e| e.file_name().to_string_lossy().to_string())
+            .map(|e| e.name.to_string_lossy().to_string())
             .collect();
         assert!(names.contains(&"subdir".to_string()));
         assert!(names.contains(&"file1.txt".to_string()));
@@ -105,16 +268,29 @@ mod tests {
         fs::write(temp_dir.path().join("apple.txt"), "content").unwrap();
         fs::write(temp_dir.path().join("banana.txt"), "content").unwrap();
 
-        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let mut explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
         let entries = explorer.ls().unwrap();
         let names: Vec<String> = entries
             .iter()
-            .map(|e| e.file_name().to_string_lossy().to_string())
+            .map(|e| e.name.to_string_lossy().to_string())
             .collect();
 
         assert_eq!(names, vec!["apple.txt", "banana.txt", "zebra.txt"]);
     }
 
+    #[test]
+    fn test_ls_honours_show_hidden() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join(".hidden"), "content").unwrap();
+        fs::write(temp_dir.path().join("visible.txt"), "content").unwrap();
+
+        let mut explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(explorer.ls().unwrap().len(), 1);
+
+        explorer.options.show_hidden = true;
+        assert_eq!(explorer.ls().unwrap().len(), 2);
+    }
+
     #[test]
     fn test_cd_to_valid_subdirectory() {
         let temp_dir = TempDir::new().unwrap();
@@ -124,7 +300,10 @@ mod tests {
         let mut explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
         let result = explorer.cd(subdir.clone());
         assert!(result.is_ok());
-        assert_eq!(explorer.cwd(), subdir.canonicalize().unwrap().to_string_lossy());
+        assert_eq!(
+            explorer.cwd(),
+            subdir.canonicalize().unwrap().to_string_lossy()
+        );
     }
 
     #[test]
@@ -160,10 +339,152 @@ mod tests {
         let mut explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
         let entries = explorer.cd(subdir).unwrap();
         assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name.to_string_lossy(), "file_in_subdir.txt");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_logical_mode_keeps_symlink_in_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let real = temp_dir.path().join("real");
+        fs::create_dir_all(real.join("child")).unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let link = root.join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        let mut explorer = Explorer::with_path_mode(root.clone(), PathMode::Logical).unwrap();
+        explorer.cd(link.clone()).unwrap();
+        assert_eq!(explorer.current_dir, link);
+
+        explorer.cd(link.join("child")).unwrap();
+        assert_eq!(explorer.current_dir, link.join("child"));
+
+        explorer.cd(PathBuf::from("..")).unwrap();
+        assert_eq!(explorer.current_dir, link);
+
+        explorer.cd(link.join("..")).unwrap();
+        assert_eq!(explorer.current_dir, root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_physical_mode_resolves_symlinks() {
+        let temp_dir = TempDir::new().unwrap();
+        let real = temp_dir.path().join("real");
+        fs::create_dir(&real).unwrap();
+        let link = temp_dir.path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        let mut explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        explorer.cd(link).unwrap();
+        assert_eq!(explorer.current_dir, real.canonicalize().unwrap());
+    }
+
+    #[test]
+    fn test_logical_cd_to_nonexistent_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut explorer =
+            Explorer::with_path_mode(temp_dir.path().to_path_buf(), PathMode::Logical).unwrap();
+        assert!(explorer.cd(temp_dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_normalize_lexically() {
         assert_eq!(
-            entries[0].file_name().to_string_lossy(),
-            "file_in_subdir.txt"
+            normalize_lexically(Path::new("/a/./b/../c/")),
+            PathBuf::from("/a/c")
         );
+        assert_eq!(normalize_lexically(Path::new("/..")), PathBuf::from("/"));
+        assert_eq!(
+            normalize_lexically(Path::new("../a/..")),
+            PathBuf::from("..")
+        );
+    }
+
+    #[test]
+    fn test_failed_cd_stays_in_current_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("file.txt");
+        fs::write(&file, "content").unwrap();
+
+        let mut explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let before = explorer.current_dir.clone();
+        assert!(explorer.cd(file).is_err());
+        assert_eq!(explorer.current_dir, before);
+    }
+
+    #[test]
+    fn test_start_cd_moves_on_enter() {
+        let temp_dir = TempDir::new().unwrap();
+        let subdir = temp_dir.path().join("subdir");
+        fs::create_dir(&subdir).unwrap();
+        fs::write(subdir.join("file.txt"), "content").unwrap();
+
+        let mut explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let before = explorer.current_dir.clone();
+        let mut loader = explorer.start_cd(PathBuf::from("subdir")).unwrap();
+        assert_eq!(explorer.current_dir, before);
+
+        loader.poll(std::time::Duration::from_secs(5)).unwrap();
+        assert_eq!(loader.entries().len(), 1);
+        explorer.enter(&loader);
+        assert_eq!(explorer.current_dir, subdir.canonicalize().unwrap());
+        assert!(explorer.start_cd(PathBuf::from("missing")).is_err());
+
+        // Once remembered, the listing is available without waiting.
+        explorer.remember(&loader);
+        let mut loader = explorer.start_ls();
+        loader.poll(std::time::Duration::ZERO).unwrap();
+        assert!(loader.is_complete());
+        assert_eq!(loader.entries().len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_revisits_are_served_from_cache_until_directory_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let subdir = temp_dir.path().join("subdir");
+        fs::create_dir(&subdir).unwrap();
+        fs::write(subdir.join("old.txt"), "content").unwrap();
+        let pinned = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1);
+        let pin = || {
+            fs::File::open(&subdir)
+                .unwrap()
+                .set_modified(pinned)
+                .unwrap()
+        };
+        pin();
+
+        let mut explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(explorer.cd(subdir.clone()).unwrap().len(), 1);
+        explorer.cd(temp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(explorer.cache.len(), 2);
+
+        // A new entry behind an unchanged mtime is not seen: the listing is
+        // not read again.
+        fs::write(subdir.join("new.txt"), "content").unwrap();
+        pin();
+        assert_eq!(explorer.cd(subdir.clone()).unwrap().len(), 1);
+
+        // A refresh reads it regardless.
+        let mut loader = explorer.start_refresh();
+        loader.poll(std::time::Duration::from_secs(5)).unwrap();
+        assert_eq!(loader.entries().len(), 2);
+
+        fs::remove_file(subdir.join("old.txt")).unwrap();
+        let entries = explorer.ls().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name.to_string_lossy(), "new.txt");
+    }
+
+    #[test]
+    fn test_cache_can_be_disabled() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut explorer = Explorer::new(temp_dir.path().to_path_buf())
+            .unwrap()
+            .with_cache_capacity(0);
+        explorer.ls().unwrap();
+        assert!(explorer.cache.is_empty());
     }
 
     #[test]
@@ -184,6 +505,9 @@ mod tests {
 
         let mut explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
         explorer.cd(subdir.clone()).unwrap();
-        assert_eq!(explorer.cwd(), subdir.canonicalize().unwrap().to_string_lossy());
+        assert_eq!(
+            explorer.cwd(),
+            subdir.canonicalize().unwrap().to_string_lossy()
+        );
     }
 }
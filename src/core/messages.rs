@@ -0,0 +1,185 @@
+//! Notifications shown in the status line.
+//!
+//! The latest [`LOG_CAPACITY`] messages are kept in a log that the user can
+//! open from the UI; the status line only shows the newest one until it
+//! expires. Stdout belongs to the shell protocol, so messages are never
+//! printed there: errors are repeated on stderr once the terminal has been
+//! restored.
+
+use std::time::{Duration, Instant};
+
+/// How many messages the log keeps; older ones are dropped.
+pub const LOG_CAPACITY: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warn => "warn",
+            Severity::Error => "error",
+        }
+    }
+
+    /// How long the status line shows a message of this severity.
+    pub fn ttl(self) -> Duration {
+        match self {
+            Severity::Info => Duration::from_secs(3),
+            Severity::Warn => Duration::from_secs(5),
+            Severity::Error => Duration::from_secs(8),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub severity: Severity,
+    pub text: String,
+    pub created: Instant,
+}
+
+impl Message {
+    fn expires(&self) -> Instant {
+        self.created + self.severity.ttl()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Messages {
+    log: Vec<Message>,
+    /// Errors that were dropped from the log, so the run still counts as
+    /// failed.
+    dropped_errors: usize,
+}
+
+impl Messages {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, severity: Severity, text: impl Into<String>, now: Instant) {
+        self.log.push(Message {
+            severity,
+            text: text.into(),
+            created: now,
+        });
+        if self.log.len() > LOG_CAPACITY {
+            let excess = self.log.len() - LOG_CAPACITY;
+            self.dropped_errors += self.log[..excess]
+                .iter()
+                .filter(|message| message.severity == Severity::Error)
+                .count();
+            self.log.drain(..excess);
+        }
+    }
+
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(Severity::Info, text, Instant::now());
+    }
+
+    pub fn warn(&mut self, text: impl Into<String>) {
+        self.push(Severity::Warn, text, Instant::now());
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(Severity::Error, text, Instant::now());
+    }
+
+    /// The newest message, unless it has expired.
+    pub fn current(&self, now: Instant) -> Option<&Message> {
+        self.log.last().filter(|message| message.expires() > now)
+    }
+
+    /// Time until the current message expires and the status line needs
+    /// redrawing.
+    pub fn timeout(&self, now: Instant) -> Option<Duration> {
+        self.current(now)
+            .map(|message| message.expires().saturating_duration_since(now))
+    }
+
+    pub fn log(&self) -> &[Message] {
+        &self.log
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Message> {
+        self.log
+            .iter()
+            .filter(|message| message.severity == Severity::Error)
+    }
+
+    /// How many errors no longer appear in [`errors`](Self::errors).
+    pub fn dropped_errors(&self) -> usize {
+        self.dropped_errors
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.dropped_errors > 0 || self.errors().next().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_message_expires() {
+        let now = Instant::now();
+        let mut messages = Messages::new();
+        messages.push(Severity::Info, "hello", now);
+
+        assert_eq!(messages.current(now).unwrap().text, "hello");
+        assert_eq!(messages.timeout(now), Some(Severity::Info.ttl()));
+        let later = now + Severity::Info.ttl();
+        assert!(messages.current(later).is_none());
+        assert!(messages.timeout(later).is_none());
+        assert_eq!(messages.log().len(), 1);
+    }
+
+    #[test]
+    fn test_newest_message_wins() {
+        let now = Instant::now();
+        let mut messages = Messages::new();
+        messages.push(Severity::Error, "first", now);
+        messages.push(Severity::Info, "second", now);
+        assert_eq!(messages.current(now).unwrap().text, "second");
+    }
+
+    #[test]
+    fn test_errors() {
+        let now = Instant::now();
+        let mut messages = Messages::new();
+        messages.push(Severity::Warn, "careful", now);
+        assert!(!messages.has_errors());
+
+        messages.push(Severity::Error, "broken", now);
+        let errors: Vec<_> = messages.errors().map(|m| m.text.as_str()).collect();
+        assert_eq!(errors, ["broken"]);
+        assert!(messages.has_errors());
+    }
+
+    #[test]
+    fn test_log_keeps_latest_messages() {
+        let now = Instant::now();
+        let mut messages = Messages::new();
+        messages.push(Severity::Error, "oldest", now);
+        for i in 0..LOG_CAPACITY {
+            messages.push(Severity::Info, format!("info {i}"), now);
+        }
+
+        assert_eq!(messages.log().len(), LOG_CAPACITY);
+        assert_eq!(messages.log()[0].text, "info 0");
+        assert_eq!(
+            messages.current(now).unwrap().text,
+            format!("info {}", LOG_CAPACITY - 1)
+        );
+        assert_eq!(messages.errors().count(), 0);
+        assert_eq!(messages.dropped_errors(), 1);
+        assert!(messages.has_errors());
+    }
+}
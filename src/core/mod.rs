@@ -1 +1,6 @@
 pub mod explorer;
+pub mod messages;
+pub mod modes;
+pub mod navigation;
+pub mod selection;
+pub mod session;
@@ -0,0 +1,323 @@
+//! Insert/Normal mode handling.
+//!
+//! In Insert mode typed characters build the fuzzy filter; in Normal mode
+//! letters are commands, and Visual mode extends a marked range as the cursor
+//! moves. Each mode maps key presses to an [`Action`] that the
+//! UI carries out, so the key bindings live here rather than in the event loop.
+
+use crate::cli::output::Command;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mode {
+    #[default]
+    Insert,
+    Normal,
+    Visual,
+}
+
+/// Something a key press asks the explorer to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    /// `ESC` in Normal mode: end a search, or clear the filter. Never exits,
+    /// except in the picker once the filter is empty.
+    Escape,
+    ChangeDirectoryAndExit,
+    ClearFilter,
+    PushFilter(char),
+    PopFilter,
+    SelectNext,
+    SelectPrevious,
+    SelectFirst,
+    SelectLast,
+    EnterDirectory,
+    ChangeToParent,
+    /// Exit with the given command applied to the highlighted entry.
+    Run(Command),
+    SwitchMode(Mode),
+    /// Clear the filter and start typing a new one.
+    NewFilter,
+    /// Toggle the mark on the highlighted entry and move down.
+    ToggleMark,
+    MarkAll,
+    InvertMarks,
+    ClearMarks,
+    /// Enter Visual mode anchored at the highlighted entry.
+    StartVisual,
+    /// Mark the Visual range and return to Normal mode.
+    EndVisual,
+    ToggleHidden,
+    CycleSort,
+    ReverseSort,
+    ToggleLongListing,
+    /// Jump to the resolved target of the highlighted symlink.
+    FollowLink,
+    /// Open the log of every message shown in the status line.
+    ShowMessages,
+    /// Hand the terminal back to the shell and stop, like Ctrl-Z.
+    Suspend,
+    /// Fuzzy-search every path below the current directory.
+    StartSearch,
+    /// Re-read the current directory from disk, skipping the cache.
+    Refresh,
+}
+
+impl Action {
+    /// Whether the action only makes sense in a directory listing, as opposed
+    /// to paths read from stdin.
+    pub fn browses_directory(&self) -> bool {
+        matches!(
+            self,
+            Action::EnterDirectory
+                | Action::ChangeToParent
+                | Action::ChangeDirectoryAndExit
+                | Action::FollowLink
+                | Action::ToggleHidden
+                | Action::CycleSort
+                | Action::ReverseSort
+                | Action::ToggleLongListing
+                | Action::StartSearch
+                | Action::Refresh
+        )
+    }
+}
+
+impl Mode {
+    /// Short name shown in the mode indicator.
+    pub fn label(self) -> &'static str {
+        match self {
+            Mode::Insert => "INSERT",
+            Mode::Normal => "NORMAL",
+            Mode::Visual => "VISUAL",
+        }
+    }
+
+    /// Key hints shown in the footer when no filter is active.
+    pub fn hint(self) -> &'static str {
+        match self {
+            Mode::Insert => {
+                "Type to filter, ↓↑ move, ←→ change directory, Enter select, ESC normal mode"
+            }
+            Mode::Normal => {
+                "j/k move, h/l change directory, Space mark, V visual, . hidden, s sort, L details, R refresh, f search, m messages, Enter select, e edit, o open, c cd here, i insert, q quit"
+            }
+            Mode::Visual => "j/k extend range, Space/V/ESC mark range, Enter select range",
+        }
+    }
+
+    /// Maps a key press to the action it triggers in this mode.
+    pub fn action(self, key: KeyEvent) -> Option<Action> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return match key.code {
+                KeyCode::Char('c') => Some(Action::Quit),
+                KeyCode::Char('z') => Some(Action::Suspend),
+                KeyCode::Char('f') => Some(Action::StartSearch),
+                KeyCode::Char('r') => Some(Action::Refresh),
+                _ => None,
+            };
+        }
+        common_action(key.code).or_else(|| match self {
+            Mode::Insert => insert_action(key.code),
+            Mode::Normal => normal_action(key.code),
+            Mode::Visual => visual_action(key.code),
+        })
+    }
+}
+
+fn common_action(code: KeyCode) -> Option<Action> {
+    Some(match code {
+        KeyCode::Down => Action::SelectNext,
+        KeyCode::Up => Action::SelectPrevious,
+        KeyCode::Home => Action::SelectFirst,
+        KeyCode::End => Action::SelectLast,
+        KeyCode::Right => Action::EnterDirectory,
+        KeyCode::Left => Action::ChangeToParent,
+        KeyCode::Enter => Action::Run(Command::Select),
+        _ => return None,
+    })
+}
+
+fn insert_action(code: KeyCode) -> Option<Action> {
+    Some(match code {
+        KeyCode::Esc => Action::SwitchMode(Mode::Normal),
+        KeyCode::Backspace => Action::PopFilter,
+        KeyCode::Tab => Action::ToggleMark,
+        KeyCode::Char(c) => Action::PushFilter(c),
+        _ => return None,
+    })
+}
+
+fn normal_action(code: KeyCode) -> Option<Action> {
+    Some(match code {
+        KeyCode::Esc => Action::Escape,
+        KeyCode::Backspace => Action::PopFilter,
+        KeyCode::Char('i') | KeyCode::Char('a') => Action::SwitchMode(Mode::Insert),
+        KeyCode::Char('/') => Action::NewFilter,
+        KeyCode::Char('j') => Action::SelectNext,
+        KeyCode::Char('k') => Action::SelectPrevious,
+        KeyCode::Char('g') => Action::SelectFirst,
+        KeyCode::Char('G') => Action::SelectLast,
+        KeyCode::Char('l') => Action::EnterDirectory,
+        KeyCode::Char('h') => Action::ChangeToParent,
+        KeyCode::Char('e') => Action::Run(Command::Edit),
+        KeyCode::Char('o') => Action::Run(Command::Open),
+        KeyCode::Char('c') => Action::ChangeDirectoryAndExit,
+        KeyCode::Char('q') | KeyCode::Char('Q') => Action::Quit,
+        KeyCode::Char(' ') | KeyCode::Tab => Action::ToggleMark,
+        KeyCode::Char('V') => Action::StartVisual,
+        KeyCode::Char('A') => Action::MarkAll,
+        KeyCode::Char('v') => Action::InvertMarks,
+        KeyCode::Char('u') => Action::ClearMarks,
+        KeyCode::Char('.') => Action::ToggleHidden,
+        KeyCode::Char('s') => Action::CycleSort,
+        KeyCode::Char('S') => Action::ReverseSort,
+        KeyCode::Char('L') => Action::ToggleLongListing,
+        KeyCode::Char('>') => Action::FollowLink,
+        KeyCode::Char('m') => Action::ShowMessages,
+        KeyCode::Char('f') => Action::StartSearch,
+        KeyCode::Char('R') => Action::Refresh,
+        _ => return None,
+    })
+}
+
+fn visual_action(code: KeyCode) -> Option<Action> {
+    Some(match code {
+        KeyCode::Esc | KeyCode::Char(' ') | KeyCode::Char('V') => Action::EndVisual,
+        KeyCode::Char('j') => Action::SelectNext,
+        KeyCode::Char('k') => Action::SelectPrevious,
+        KeyCode::Char('g') => Action::SelectFirst,
+        KeyCode::Char('G') => Action::SelectLast,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_insert_mode_letters_filter() {
+        assert_eq!(
+            Mode::Insert.action(key(KeyCode::Char('q'))),
+            Some(Action::PushFilter('q'))
+        );
+        assert_eq!(
+            Mode::Insert.action(KeyEvent::new(KeyCode::Char('Q'), KeyModifiers::SHIFT)),
+            Some(Action::PushFilter('Q'))
+        );
+    }
+
+    #[test]
+    fn test_normal_mode_letters_are_commands() {
+        assert_eq!(
+            Mode::Normal.action(key(KeyCode::Char('q'))),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            Mode::Normal.action(key(KeyCode::Char('j'))),
+            Some(Action::SelectNext)
+        );
+        assert_eq!(
+            Mode::Normal.action(key(KeyCode::Char('e'))),
+            Some(Action::Run(Command::Edit))
+        );
+        assert_eq!(
+            Mode::Normal.action(key(KeyCode::Char('.'))),
+            Some(Action::ToggleHidden)
+        );
+        assert_eq!(
+            Mode::Normal.action(key(KeyCode::Char('m'))),
+            Some(Action::ShowMessages)
+        );
+        assert_eq!(Mode::Normal.action(key(KeyCode::Char('z'))), None);
+    }
+
+    #[test]
+    fn test_mode_switching_keys() {
+        assert_eq!(
+            Mode::Insert.action(key(KeyCode::Esc)),
+            Some(Action::SwitchMode(Mode::Normal))
+        );
+        assert_eq!(
+            Mode::Normal.action(key(KeyCode::Char('i'))),
+            Some(Action::SwitchMode(Mode::Insert))
+        );
+        assert_eq!(Mode::Normal.action(key(KeyCode::Esc)), Some(Action::Escape));
+    }
+
+    #[test]
+    fn test_arrow_keys_work_in_both_modes() {
+        for mode in [Mode::Insert, Mode::Normal] {
+            assert_eq!(mode.action(key(KeyCode::Down)), Some(Action::SelectNext));
+            assert_eq!(
+                mode.action(key(KeyCode::Right)),
+                Some(Action::EnterDirectory)
+            );
+            assert_eq!(
+                mode.action(key(KeyCode::Enter)),
+                Some(Action::Run(Command::Select))
+            );
+        }
+    }
+
+    #[test]
+    fn test_ctrl_c_quits_in_both_modes() {
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(Mode::Insert.action(ctrl_c), Some(Action::Quit));
+        assert_eq!(Mode::Normal.action(ctrl_c), Some(Action::Quit));
+
+        let ctrl_z = KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL);
+        assert_eq!(Mode::Insert.action(ctrl_z), Some(Action::Suspend));
+
+        let ctrl_f = KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL);
+        assert_eq!(Mode::Insert.action(ctrl_f), Some(Action::StartSearch));
+        assert_eq!(
+            Mode::Normal.action(key(KeyCode::Char('f'))),
+            Some(Action::StartSearch)
+        );
+
+        let ctrl_r = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+        assert_eq!(Mode::Insert.action(ctrl_r), Some(Action::Refresh));
+        assert_eq!(
+            Mode::Normal.action(key(KeyCode::Char('R'))),
+            Some(Action::Refresh)
+        );
+    }
+
+    #[test]
+    fn test_mark_keys() {
+        assert_eq!(
+            Mode::Normal.action(key(KeyCode::Char(' '))),
+            Some(Action::ToggleMark)
+        );
+        assert_eq!(
+            Mode::Insert.action(key(KeyCode::Tab)),
+            Some(Action::ToggleMark)
+        );
+        assert_eq!(
+            Mode::Normal.action(key(KeyCode::Char('V'))),
+            Some(Action::StartVisual)
+        );
+        assert_eq!(
+            Mode::Visual.action(key(KeyCode::Esc)),
+            Some(Action::EndVisual)
+        );
+        assert_eq!(
+            Mode::Visual.action(key(KeyCode::Char('j'))),
+            Some(Action::SelectNext)
+        );
+        assert_eq!(Mode::Visual.action(key(KeyCode::Char('q'))), None);
+    }
+
+    #[test]
+    fn test_mode_labels() {
+        assert_eq!(Mode::Insert.label(), "INSERT");
+        assert_eq!(Mode::Normal.label(), "NORMAL");
+        assert_eq!(Mode::Visual.label(), "VISUAL");
+    }
+}
@@ -0,0 +1,101 @@
+//! Automatic navigation while filtering.
+//!
+//! When the filter narrows the listing down to a single directory, breeze
+//! enters it and clears the filter. The move waits for a short grace delay so
+//! that a user who is still typing is not thrown into a directory they were
+//! typing past; every new key press re-arms or cancels the timer.
+
+use crate::fs::ObjectType;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_AUTO_CD_DELAY: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone)]
+pub struct AutoCd {
+    delay: Duration,
+    due: Option<Instant>,
+}
+
+impl AutoCd {
+    pub fn new(delay: Duration) -> Self {
+        Self { delay, due: None }
+    }
+
+    /// Arms the timer if `kinds` (the filtered listing) is exactly one
+    /// directory, and cancels it otherwise.
+    pub fn update<I: IntoIterator<Item = ObjectType>>(&mut self, kinds: I, now: Instant) {
+        let mut kinds = kinds.into_iter();
+        self.due = match (kinds.next(), kinds.next()) {
+            (Some(kind), None) if kind.is_dir() => Some(now + self.delay),
+            _ => None,
+        };
+    }
+
+    pub fn cancel(&mut self) {
+        self.due = None;
+    }
+
+    /// Time left before the pending move, if one is armed.
+    pub fn timeout(&self, now: Instant) -> Option<Duration> {
+        self.due.map(|due| due.saturating_duration_since(now))
+    }
+
+    /// Disarms the timer and returns whether it had expired.
+    pub fn take_due(&mut self, now: Instant) -> bool {
+        match self.due {
+            Some(due) if due <= now => {
+                self.due = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arms_only_for_single_directory() {
+        let now = Instant::now();
+        let mut auto_cd = AutoCd::new(Duration::from_millis(100));
+
+        auto_cd.update([ObjectType::File], now);
+        assert_eq!(auto_cd.timeout(now), None);
+
+        auto_cd.update([ObjectType::Directory, ObjectType::Directory], now);
+        assert_eq!(auto_cd.timeout(now), None);
+
+        auto_cd.update([ObjectType::Directory], now);
+        assert_eq!(auto_cd.timeout(now), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn test_fires_after_delay() {
+        let now = Instant::now();
+        let mut auto_cd = AutoCd::new(Duration::from_millis(100));
+        auto_cd.update([ObjectType::Directory], now);
+
+        assert!(!auto_cd.take_due(now + Duration::from_millis(50)));
+        assert!(auto_cd.take_due(now + Duration::from_millis(100)));
+        assert!(!auto_cd.take_due(now + Duration::from_millis(200)));
+    }
+
+    #[test]
+    fn test_zero_delay_is_due_immediately() {
+        let now = Instant::now();
+        let mut auto_cd = AutoCd::new(Duration::ZERO);
+        auto_cd.update([ObjectType::Directory], now);
+        assert!(auto_cd.take_due(now));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let now = Instant::now();
+        let mut auto_cd = AutoCd::new(Duration::ZERO);
+        auto_cd.update([ObjectType::Directory], now);
+        auto_cd.cancel();
+        assert!(!auto_cd.take_due(now));
+    }
+}
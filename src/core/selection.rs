@@ -0,0 +1,111 @@
+//! Marked entries for multi-selection.
+//!
+//! Marks are keyed by absolute path rather than list position, so they survive
+//! filtering and moving between directories.
+
+use crate::cli::output::Item;
+use crate::fs::ObjectType;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default)]
+pub struct Selection {
+    marks: BTreeMap<PathBuf, ObjectType>,
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.marks.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.marks.len()
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.marks.contains_key(path)
+    }
+
+    pub fn insert(&mut self, path: PathBuf, kind: ObjectType) {
+        self.marks.insert(path, kind);
+    }
+
+    /// Marks `path` if it is unmarked and unmarks it otherwise.
+    pub fn toggle(&mut self, path: PathBuf, kind: ObjectType) {
+        if self.marks.remove(&path).is_none() {
+            self.marks.insert(path, kind);
+        }
+    }
+
+    /// Toggles every given entry, leaving marks outside `entries` alone.
+    pub fn invert<I: IntoIterator<Item = (PathBuf, ObjectType)>>(&mut self, entries: I) {
+        for (path, kind) in entries {
+            self.toggle(path, kind);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.marks.clear();
+    }
+
+    /// Marked entries in path order.
+    pub fn items(&self) -> Vec<Item> {
+        self.marks
+            .iter()
+            .map(|(path, kind)| Item::new(path.clone(), *kind))
+            .collect()
+    }
+}
+
+impl Extend<(PathBuf, ObjectType)> for Selection {
+    fn extend<I: IntoIterator<Item = (PathBuf, ObjectType)>>(&mut self, entries: I) {
+        self.marks.extend(entries);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str) -> (PathBuf, ObjectType) {
+        (PathBuf::from(path), ObjectType::File)
+    }
+
+    #[test]
+    fn test_toggle_marks_and_unmarks() {
+        let mut selection = Selection::new();
+        selection.toggle(PathBuf::from("/a"), ObjectType::File);
+        assert!(selection.contains(Path::new("/a")));
+        selection.toggle(PathBuf::from("/a"), ObjectType::File);
+        assert!(selection.is_empty());
+    }
+
+    #[test]
+    fn test_invert_only_touches_given_entries() {
+        let mut selection = Selection::new();
+        selection.extend([entry("/a"), entry("/elsewhere")]);
+        selection.invert([entry("/a"), entry("/b")]);
+
+        let paths: Vec<PathBuf> = selection.items().into_iter().map(|i| i.path).collect();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("/b"), PathBuf::from("/elsewhere")]
+        );
+    }
+
+    #[test]
+    fn test_items_are_in_path_order_with_kinds() {
+        let mut selection = Selection::new();
+        selection.insert(PathBuf::from("/z"), ObjectType::File);
+        selection.insert(PathBuf::from("/a"), ObjectType::Directory);
+
+        let items = selection.items();
+        assert_eq!(selection.len(), 2);
+        assert_eq!(items[0], Item::new("/a", ObjectType::Directory));
+        assert_eq!(items[1], Item::new("/z", ObjectType::File));
+    }
+}
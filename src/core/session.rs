@@ -0,0 +1,220 @@
+//! Persisted explorer state for the shell wrapper's session loop.
+//!
+//! The wrapper passes the same `--resume <session>` file on every iteration:
+//! breeze restores the directory, filter and highlighted entry from it at
+//! startup and writes them back on exit.
+
+use crate::error::ExplorerError;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    /// Stored as hex-encoded bytes so a directory whose path is not valid
+    /// UTF-8 comes back unchanged.
+    #[serde(with = "hex_path")]
+    pub cwd: PathBuf,
+    pub filter: String,
+    /// Name of the highlighted entry, if any, hex-encoded like `cwd`.
+    #[serde(default, with = "hex_name")]
+    pub selected: Option<OsString>,
+}
+
+impl Session {
+    /// Loads a session, returning `None` if the file does not exist or is
+    /// empty (e.g. freshly created by `mktemp`).
+    pub fn load(path: &Path) -> Result<Option<Self>, ExplorerError> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        if contents.trim().is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&contents)?))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ExplorerError> {
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// Hex encoding of OS strings, so paths and names that are not valid UTF-8
+/// survive the JSON file unchanged.
+mod hex {
+    use std::ffi::{OsStr, OsString};
+
+    pub fn encode(name: &OsStr) -> String {
+        to_bytes(name).iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    pub fn decode(hex: &str) -> Result<OsString, String> {
+        if !hex.is_ascii() || !hex.len().is_multiple_of(2) {
+            return Err(format!("{hex:?} is not hex-encoded"));
+        }
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|err| err.to_string())?;
+        Ok(from_bytes(bytes))
+    }
+
+    #[cfg(unix)]
+    fn to_bytes(name: &OsStr) -> Vec<u8> {
+        use std::os::unix::ffi::OsStrExt;
+        name.as_bytes().to_vec()
+    }
+
+    #[cfg(not(unix))]
+    fn to_bytes(name: &OsStr) -> Vec<u8> {
+        name.to_string_lossy().into_owned().into_bytes()
+    }
+
+    #[cfg(unix)]
+    fn from_bytes(bytes: Vec<u8>) -> OsString {
+        use std::os::unix::ffi::OsStringExt;
+        OsString::from_vec(bytes)
+    }
+
+    #[cfg(not(unix))]
+    fn from_bytes(bytes: Vec<u8>) -> OsString {
+        String::from_utf8_lossy(&bytes).into_owned().into()
+    }
+}
+
+mod hex_path {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::path::{Path, PathBuf};
+
+    pub fn serialize<S: Serializer>(path: &Path, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&super::hex::encode(path.as_os_str()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<PathBuf, D::Error> {
+        let hex = String::deserialize(d)?;
+        super::hex::decode(&hex)
+            .map(PathBuf::from)
+            .map_err(D::Error::custom)
+    }
+}
+
+mod hex_name {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::ffi::OsString;
+
+    pub fn serialize<S: Serializer>(name: &Option<OsString>, s: S) -> Result<S::Ok, S::Error> {
+        match name {
+            Some(name) => s.serialize_some(&super::hex::encode(name)),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<OsString>, D::Error> {
+        Option::<String>::deserialize(d)?
+            .map(|hex| super::hex::decode(&hex).map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sample_session() -> Session {
+        Session {
+            cwd: PathBuf::from("/some/dir"),
+            filter: "src".to_string(),
+            selected: Some("src".into()),
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("session");
+        let session = sample_session();
+
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap(), Some(session));
+    }
+
+    #[test]
+    fn test_cwd_and_selected_name_are_stored_as_hex() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("session");
+        sample_session().save(&path).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["selected"], "737263");
+        assert_eq!(json["cwd"], "2f736f6d652f646972");
+
+        fs::write(&path, r#"{"cwd":"2f","filter":"","selected":"7g"}"#).unwrap();
+        assert!(Session::load(&path).is_err());
+        fs::write(&path, r#"{"cwd":"/","filter":""}"#).unwrap();
+        assert!(Session::load(&path).is_err());
+        fs::write(&path, r#"{"cwd":"2f","filter":""}"#).unwrap();
+        assert_eq!(Session::load(&path).unwrap().unwrap().selected, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_selection_round_trips() {
+        use std::os::unix::ffi::OsStringExt;
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("session");
+        let session = Session {
+            selected: Some(OsString::from_vec(b"caf\xe9".to_vec())),
+            ..sample_session()
+        };
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap(), Some(session));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_cwd_round_trips() {
+        use std::os::unix::ffi::OsStringExt;
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("session");
+        let session = Session {
+            cwd: PathBuf::from(OsString::from_vec(b"/tmp/caf\xe9".to_vec())),
+            ..sample_session()
+        };
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap(), Some(session));
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let result = Session::load(&temp_dir.path().join("missing")).unwrap();
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_load_empty_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("session");
+        fs::write(&path, "").unwrap();
+        assert_eq!(Session::load(&path).unwrap(), None);
+    }
+
+    #[test]
+    fn test_load_invalid_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("session");
+        fs::write(&path, "not json").unwrap();
+        assert!(matches!(
+            Session::load(&path),
+            Err(ExplorerError::Session(_))
+        ));
+    }
+}
@@ -7,4 +7,10 @@ pub enum ExplorerError {
 
     #[error("Invalid directory: {0}")]
     InvalidDirectory(String),
+
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    #[error("Session file error: {0}")]
+    Session(#[from] serde_json::Error),
 }
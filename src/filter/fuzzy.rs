@@ -0,0 +1,262 @@
+//! Incremental fuzzy matching on a background thread pool.
+//!
+//! [`Fuzzy`] wraps a `nucleo` worker: candidates are injected once, and every
+//! change of the query is matched off the UI thread. A query that only grows
+//! narrows the previous matches instead of scoring every candidate again, and
+//! [`Fuzzy::tick`] waits at most the given time before the caller draws
+//! whatever results are ready.
+
+use nucleo::pattern::{CaseMatching, Normalization, Pattern};
+use nucleo::{Config, Injector, Matcher, Nucleo, Utf32Str, Utf32String};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How long a key press waits for the matcher before drawing what it has.
+pub const MATCH_WAIT_MS: u64 = 10;
+
+pub struct Fuzzy<T: Send + Sync + 'static> {
+    nucleo: Nucleo<T>,
+    injector: Injector<T>,
+    query: String,
+    running: bool,
+    /// Computes match positions for highlighting, which the workers skip.
+    matcher: Matcher,
+}
+
+impl<T: Send + Sync + 'static> Fuzzy<T> {
+    pub fn new() -> Self {
+        let config = Config::DEFAULT.match_paths();
+        let nucleo = Nucleo::new(config.clone(), Arc::new(|| {}), None, 1);
+        Self {
+            injector: nucleo.injector(),
+            nucleo,
+            query: String::new(),
+            running: false,
+            matcher: Matcher::new(config),
+        }
+    }
+
+    /// Drops every candidate.
+    pub fn clear(&mut self) {
+        self.nucleo.restart(true);
+        self.injector = self.nucleo.injector();
+        self.running = true;
+    }
+
+    /// Adds a candidate matched on `text`.
+    pub fn push(&mut self, item: T, text: &str) {
+        self.injector.push(item, |_, columns| {
+            columns[0] = Utf32String::from(text);
+        });
+        self.running = true;
+    }
+
+    /// Number of candidates pushed since the last [`clear`](Self::clear).
+    pub fn len(&self) -> usize {
+        self.injector.injected_items() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn set_query(&mut self, query: &str) {
+        if query == self.query {
+            return;
+        }
+        let append = query.starts_with(self.query.as_str());
+        self.nucleo
+            .pattern
+            .reparse(0, query, CaseMatching::Ignore, Normalization::Smart, append);
+        self.query = query.to_string();
+        self.running = true;
+    }
+
+    /// Lets the workers pick up new candidates or a new query, waiting up to
+    /// `timeout_ms` for them to finish. Returns whether the matches changed.
+    pub fn tick(&mut self, timeout_ms: u64) -> bool {
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        let mut changed = false;
+        // New candidates are only handed to the workers by one tick and
+        // collected by the next, so keep ticking until the deadline.
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let status = self.nucleo.tick(remaining.as_millis() as u64);
+            changed |= status.changed;
+            self.running = status.running;
+            if !self.running || remaining.is_zero() {
+                return changed;
+            }
+        }
+    }
+
+    /// Whether the matches may still change: the query or the candidates
+    /// changed and no tick has seen the workers finish since.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn matched_count(&self) -> usize {
+        self.nucleo.snapshot().matched_item_count() as usize
+    }
+
+    /// Number of matches, or of all candidates when the query is empty.
+    pub fn count(&self) -> usize {
+        if self.query.is_empty() {
+            self.len()
+        } else {
+            self.matched_count()
+        }
+    }
+
+    /// The `n`th match, best first. With an empty query, the `n`th candidate
+    /// in the order it was pushed, including ones no tick has seen yet.
+    pub fn get(&self, n: usize) -> Option<&T> {
+        let n = u32::try_from(n).ok()?;
+        let item = if self.query.is_empty() {
+            self.injector.get(n)
+        } else {
+            self.nucleo.snapshot().get_matched_item(n)
+        };
+        item.map(|item| item.data)
+    }
+
+    /// The matched character positions of the `n`th match, for
+    /// highlighting it.
+    pub fn indices(&mut self, n: usize) -> Vec<u32> {
+        let snapshot = self.nucleo.snapshot();
+        match u32::try_from(n)
+            .ok()
+            .and_then(|n| snapshot.get_matched_item(n))
+        {
+            Some(item) if !self.query.is_empty() => indices(
+                snapshot.pattern().column_pattern(0),
+                item.matcher_columns[0].slice(..),
+                &mut self.matcher,
+            ),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Up to `limit` matches, best first, with the matched character
+    /// positions of each.
+    pub fn matches(&mut self, limit: usize) -> Vec<(&T, Vec<u32>)> {
+        let snapshot = self.nucleo.snapshot();
+        let pattern = snapshot.pattern().column_pattern(0);
+        let end = snapshot.matched_item_count().min(limit as u32);
+        snapshot
+            .matched_items(..end)
+            .map(|item| {
+                let text = item.matcher_columns[0].slice(..);
+                (item.data, indices(pattern, text, &mut self.matcher))
+            })
+            .collect()
+    }
+}
+
+fn indices(pattern: &Pattern, text: Utf32Str, matcher: &mut Matcher) -> Vec<u32> {
+    let mut indices = Vec::new();
+    pattern.indices(text, matcher, &mut indices);
+    indices.sort_unstable();
+    indices.dedup();
+    indices
+}
+
+impl<T: Send + Sync + 'static> Default for Fuzzy<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settled(fuzzy: &mut Fuzzy<String>) -> Vec<String> {
+        fuzzy.tick(5_000);
+        fuzzy
+            .matches(usize::MAX)
+            .into_iter()
+            .map(|(item, _)| item.clone())
+            .collect()
+    }
+
+    fn fuzzy(names: &[&str]) -> Fuzzy<String> {
+        let mut fuzzy = Fuzzy::new();
+        for name in names {
+            fuzzy.push(name.to_string(), name);
+        }
+        fuzzy
+    }
+
+    #[test]
+    fn test_matches_are_ranked() {
+        let mut fuzzy = fuzzy(&["domain_test.rs", "gamma.txt", "main.rs"]);
+        fuzzy.set_query("main");
+        assert_eq!(settled(&mut fuzzy), vec!["main.rs", "domain_test.rs"]);
+        assert_eq!(fuzzy.matched_count(), 2);
+    }
+
+    #[test]
+    fn test_query_can_narrow_and_widen() {
+        let mut fuzzy = fuzzy(&["main.rs", "mod.rs", "lib.rs"]);
+        fuzzy.set_query("m");
+        assert_eq!(settled(&mut fuzzy).len(), 2);
+        fuzzy.set_query("ma");
+        assert_eq!(settled(&mut fuzzy), vec!["main.rs"]);
+        fuzzy.set_query("");
+        assert_eq!(settled(&mut fuzzy).len(), 3);
+    }
+
+    #[test]
+    fn test_running_until_a_tick_settles() {
+        let mut fuzzy = fuzzy(&["one"]);
+        assert!(fuzzy.is_running());
+        settled(&mut fuzzy);
+        assert!(!fuzzy.is_running());
+        fuzzy.set_query("o");
+        assert!(fuzzy.is_running());
+        settled(&mut fuzzy);
+        assert!(!fuzzy.is_running());
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut fuzzy = fuzzy(&["one", "two"]);
+        fuzzy.clear();
+        assert!(fuzzy.is_empty());
+        fuzzy.push("three".to_string(), "three");
+        fuzzy.set_query("t");
+        assert_eq!(settled(&mut fuzzy), vec!["three"]);
+    }
+
+    #[test]
+    fn test_match_indices() {
+        let mut fuzzy = fuzzy(&["readme.md"]);
+        fuzzy.set_query("rme");
+        settled(&mut fuzzy);
+        let matches = fuzzy.matches(10);
+        assert_eq!(matches[0].1, vec![0, 4, 5]);
+        assert_eq!(fuzzy.indices(0), vec![0, 4, 5]);
+        assert!(fuzzy.indices(1).is_empty());
+    }
+
+    #[test]
+    fn test_get_reads_matches_in_place() {
+        let mut fuzzy = fuzzy(&["domain_test.rs", "gamma.txt", "main.rs"]);
+        // Without a query every candidate is listed at once, in order.
+        assert_eq!(fuzzy.count(), 3);
+        assert_eq!(fuzzy.get(1).map(String::as_str), Some("gamma.txt"));
+        assert!(fuzzy.indices(0).is_empty());
+
+        fuzzy.set_query("main");
+        settled(&mut fuzzy);
+        assert_eq!(fuzzy.count(), 2);
+        assert_eq!(fuzzy.get(0).map(String::as_str), Some("main.rs"));
+        assert_eq!(fuzzy.get(2), None);
+    }
+}
@@ -0,0 +1,162 @@
+//! Recursive fuzzy search below a directory.
+//!
+//! A [`Search`] feeds the paths streamed in by a background [`Walk`] to a
+//! [`Fuzzy`] matcher as they arrive, matching the whole path relative
+//! to the search root so that directory names count too.
+
+use super::fuzzy::Fuzzy;
+use crate::fs::{Found, ObjectType, Walk, WalkOptions};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Most hits ranked for display at once.
+pub const MAX_HITS: usize = 1000;
+
+/// How often the UI takes in newly found paths while the walk runs.
+pub const REFRESH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A path matching the search, relative to the search root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hit {
+    pub path: PathBuf,
+    pub kind: ObjectType,
+    /// Matched character positions in the path, for highlighting.
+    pub indices: Vec<u32>,
+}
+
+pub struct Search {
+    root: PathBuf,
+    walk: Walk,
+    fuzzy: Fuzzy<Found>,
+}
+
+impl Search {
+    pub fn start(root: PathBuf, options: WalkOptions) -> Self {
+        Self {
+            walk: Walk::start(root.clone(), options),
+            root,
+            fuzzy: Fuzzy::new(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Hands the paths found since the last call to the matcher and returns
+    /// whether there were any.
+    pub fn poll(&mut self) -> bool {
+        let found = self.walk.try_next();
+        let any = !found.is_empty();
+        for found in found {
+            let text = found.path.to_string_lossy().to_string();
+            self.fuzzy.push(found, &text);
+        }
+        any
+    }
+
+    /// Whether the whole subtree has been walked.
+    pub fn is_finished(&self) -> bool {
+        self.walk.is_finished()
+    }
+
+    /// Number of paths found so far.
+    pub fn len(&self) -> usize {
+        self.fuzzy.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fuzzy.is_empty()
+    }
+
+    pub fn set_query(&mut self, query: &str) {
+        self.fuzzy.set_query(query);
+    }
+
+    /// See [`Fuzzy::tick`].
+    pub fn tick(&mut self, timeout_ms: u64) -> bool {
+        self.fuzzy.tick(timeout_ms)
+    }
+
+    /// Whether ranking is still in progress.
+    pub fn is_matching(&self) -> bool {
+        self.fuzzy.is_running()
+    }
+
+    /// Up to `limit` paths matching the query, best first.
+    pub fn hits(&mut self, limit: usize) -> Vec<Hit> {
+        self.fuzzy
+            .matches(limit)
+            .into_iter()
+            .map(|(found, indices)| Hit {
+                path: found.path.clone(),
+                kind: found.kind,
+                indices,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn finished_search(root: &Path) -> Search {
+        let mut search = Search::start(root.to_path_buf(), WalkOptions::default());
+        while !search.is_finished() {
+            search.poll();
+            std::thread::yield_now();
+        }
+        search.poll();
+        search
+    }
+
+    fn hits(search: &mut Search, query: &str, limit: usize) -> Vec<Hit> {
+        search.set_query(query);
+        search.tick(5_000);
+        search.hits(limit)
+    }
+
+    fn hit_paths(search: &mut Search, query: &str) -> Vec<PathBuf> {
+        hits(search, query, MAX_HITS)
+            .into_iter()
+            .map(|hit| hit.path)
+            .collect()
+    }
+
+    #[test]
+    fn test_hits_match_relative_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("src/core")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("docs")).unwrap();
+        fs::write(temp_dir.path().join("src/core/explorer.rs"), "").unwrap();
+        fs::write(temp_dir.path().join("docs/explorer.md"), "").unwrap();
+
+        let mut search = finished_search(temp_dir.path());
+        assert_eq!(search.len(), 5);
+        assert_eq!(
+            hit_paths(&mut search, "src explorer"),
+            vec![PathBuf::from("src/core/explorer.rs")]
+        );
+
+        let hits = hit_paths(&mut search, "explorer");
+        assert_eq!(hits.len(), 2);
+        assert!(hit_paths(&mut search, "nothing-like-this").is_empty());
+    }
+
+    #[test]
+    fn test_hits_are_limited_and_highlighted() {
+        let temp_dir = TempDir::new().unwrap();
+        for i in 0..5 {
+            fs::write(temp_dir.path().join(format!("file{i}.txt")), "").unwrap();
+        }
+        let mut search = finished_search(temp_dir.path());
+
+        let hits = hits(&mut search, "file", 2);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].indices, vec![0, 1, 2, 3]);
+        assert_eq!(hits[0].kind, ObjectType::File);
+    }
+}
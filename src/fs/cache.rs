@@ -0,0 +1,278 @@
+//! Directory listings kept in memory between visits.
+//!
+//! A [`ListingCache`] maps a directory to the entries last read from it. A
+//! cached listing is only used while the directory's inode and modification
+//! time are unchanged, which catches entries being added, removed or renamed.
+//! Changes inside an entry, such as a file growing, leave the directory alone,
+//! so the entries' metadata may be out of date until it changes;
+//! [`remove`](ListingCache::remove) forces the next visit to read the
+//! directory. The least recently used listings are dropped to stay within a
+//! memory bound.
+
+use super::metadata::Entry;
+use std::collections::HashMap;
+use std::fs;
+use std::mem::{size_of, size_of_val};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const DEFAULT_CAPACITY_MIB: usize = 32;
+
+/// What a directory looked like when it was listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stamp {
+    modified: SystemTime,
+    /// Device and inode number, so a directory replaced by another one with
+    /// the same mtime is noticed; `None` on platforms without them.
+    inode: Option<(u64, u64)>,
+}
+
+impl Stamp {
+    /// Stamp of the directory at `path`, or `None` if it cannot be read or
+    /// the platform has no modification times.
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        #[cfg(unix)]
+        let inode = {
+            use std::os::unix::fs::MetadataExt;
+            Some((metadata.dev(), metadata.ino()))
+        };
+        #[cfg(not(unix))]
+        let inode = None;
+        Some(Stamp {
+            modified: metadata.modified().ok()?,
+            inode,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Cached {
+    stamp: Stamp,
+    entries: Vec<Entry>,
+    size: usize,
+    last_used: u64,
+}
+
+#[derive(Debug)]
+pub struct ListingCache {
+    /// Listings with and without dotfiles are kept apart, so toggling them
+    /// does not throw the other one away.
+    listings: HashMap<(PathBuf, bool), Cached>,
+    /// Upper bound on the estimated size of all listings, in bytes.
+    capacity: usize,
+    size: usize,
+    clock: u64,
+}
+
+impl ListingCache {
+    /// A cache holding up to `capacity` bytes of listings; 0 disables it.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            listings: HashMap::new(),
+            capacity,
+            size: 0,
+            clock: 0,
+        }
+    }
+
+    /// Number of cached directories.
+    pub fn len(&self) -> usize {
+        self.listings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.listings.is_empty()
+    }
+
+    /// Estimated size of all cached listings, in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The listing of `path` read with the same `show_hidden`, if the
+    /// directory still matches `stamp`. Stale listings are dropped.
+    pub fn get(&mut self, path: &Path, stamp: Stamp, show_hidden: bool) -> Option<&[Entry]> {
+        let key = (path.to_path_buf(), show_hidden);
+        if self.listings.get(&key)?.stamp != stamp {
+            self.remove(path);
+            return None;
+        }
+        self.clock += 1;
+        let cached = self.listings.get_mut(&key)?;
+        cached.last_used = self.clock;
+        Some(&cached.entries)
+    }
+
+    /// Remembers `entries` as the listing of `path`, dropping the least
+    /// recently used listings to make room. Listings larger than the whole
+    /// cache are not kept.
+    pub fn insert(&mut self, path: PathBuf, stamp: Stamp, show_hidden: bool, entries: Vec<Entry>) {
+        let key = (path, show_hidden);
+        self.remove_key(&key);
+        let (path, _) = &key;
+        let size = listing_size(path, &entries);
+        if size > self.capacity {
+            return;
+        }
+        while self.size + size > self.capacity {
+            let Some(oldest) = self
+                .listings
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            self.remove_key(&oldest);
+        }
+        self.clock += 1;
+        self.size += size;
+        self.listings.insert(
+            key,
+            Cached {
+                stamp,
+                entries,
+                size,
+                last_used: self.clock,
+            },
+        );
+    }
+
+    /// Drops the listings of `path`, so the next visit reads it again.
+    pub fn remove(&mut self, path: &Path) {
+        for show_hidden in [false, true] {
+            self.remove_key(&(path.to_path_buf(), show_hidden));
+        }
+    }
+
+    fn remove_key(&mut self, key: &(PathBuf, bool)) {
+        if let Some(cached) = self.listings.remove(key) {
+            self.size -= cached.size;
+        }
+    }
+}
+
+impl Default for ListingCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY_MIB << 20)
+    }
+}
+
+/// Rough memory use of a listing: the entries themselves plus the names and
+/// paths they own.
+fn listing_size(path: &Path, entries: &[Entry]) -> usize {
+    let owned: usize = entries
+        .iter()
+        .map(|entry| {
+            entry.name.len()
+                + entry.path.as_os_str().len()
+                + entry
+                    .symlink_target
+                    .as_ref()
+                    .map_or(0, |target| target.path.as_os_str().len())
+        })
+        .sum();
+    size_of::<Cached>() + path.as_os_str().len() + size_of_val(entries) + owned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn stamp(seconds: u64) -> Stamp {
+        Stamp {
+            modified: SystemTime::UNIX_EPOCH + Duration::from_secs(seconds),
+            inode: Some((1, 2)),
+        }
+    }
+
+    fn entries(dir: &Path, names: &[&str]) -> Vec<Entry> {
+        names
+            .iter()
+            .map(|name| {
+                let path = dir.join(name);
+                fs::write(&path, "").unwrap();
+                Entry::from_path(path).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_get_requires_matching_stamp_and_options() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let mut cache = ListingCache::default();
+        cache.insert(dir.to_path_buf(), stamp(1), false, entries(dir, &["a"]));
+
+        assert_eq!(cache.get(dir, stamp(1), false).unwrap().len(), 1);
+        assert!(cache.get(dir, stamp(1), true).is_none());
+        // Asking for the other dotfile setting leaves the listing alone.
+        assert_eq!(cache.len(), 1);
+
+        cache.insert(
+            dir.to_path_buf(),
+            stamp(1),
+            true,
+            entries(dir, &["a", ".b"]),
+        );
+        assert_eq!(cache.get(dir, stamp(1), true).unwrap().len(), 2);
+        assert_eq!(cache.get(dir, stamp(1), false).unwrap().len(), 1);
+
+        // A changed directory drops both listings.
+        assert!(cache.get(dir, stamp(2), false).is_none());
+        assert!(cache.is_empty());
+        assert_eq!(cache.size(), 0);
+        assert!(
+            cache
+                .get(Path::new("/elsewhere"), stamp(1), false)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_least_recently_used_listing_is_evicted() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let listing = entries(dir, &["a", "b"]);
+        let size = listing_size(&dir.join("one"), &listing);
+        let mut cache = ListingCache::new(size * 2);
+
+        cache.insert(dir.join("one"), stamp(1), false, listing.clone());
+        cache.insert(dir.join("two"), stamp(1), false, listing.clone());
+        assert!(cache.get(&dir.join("one"), stamp(1), false).is_some());
+        cache.insert(dir.join("six"), stamp(1), false, listing);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.size() <= size * 2);
+        assert!(cache.get(&dir.join("one"), stamp(1), false).is_some());
+        assert!(cache.get(&dir.join("two"), stamp(1), false).is_none());
+    }
+
+    #[test]
+    fn test_zero_capacity_caches_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        let mut cache = ListingCache::new(0);
+        cache.insert(dir.to_path_buf(), stamp(1), false, entries(dir, &["a"]));
+        assert!(cache.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stamp_changes_with_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        // Backdate the directory so the change is visible even on coarse clocks.
+        let earlier = SystemTime::UNIX_EPOCH + Duration::from_secs(1);
+        fs::File::open(dir).unwrap().set_modified(earlier).unwrap();
+        let before = Stamp::of(dir).unwrap();
+        assert_eq!(Stamp::of(dir), Some(before));
+
+        fs::write(dir.join("new"), "").unwrap();
+        assert_ne!(Stamp::of(dir), Some(before));
+        assert!(Stamp::of(&dir.join("missing")).is_none());
+    }
+}
@@ -1,19 +1,204 @@
 use crate::error::ExplorerError;
-use std::fs::{self, DirEntry};
+use crate::fs::metadata::Entry;
+use clap::ValueEnum;
+use std::cmp::Ordering;
+use std::ffi::OsStr;
+use std::fs::{self, DirEntry, ReadDir};
+use std::io;
 use std::path::Path;
 
-pub fn list_directory<P: AsRef<Path>>(path: P) -> Result<Vec<DirEntry>, ExplorerError> {
-    let entries = fs::read_dir(path)?;
-    let mut files = Vec::new();
+/// Controls which entries [`list_directory`] returns and in what order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ListOptions {
+    /// Include dotfiles.
+    pub show_hidden: bool,
+    pub sort: SortOrder,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SortOrder {
+    pub key: SortKey,
+    pub reverse: bool,
+    /// List directories before everything else, regardless of `reverse`.
+    pub dirs_first: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    /// Byte order of the name, so `Zebra` sorts before `apple`
+    #[default]
+    Name,
+    /// Numbers inside names compare by value, so `file2` sorts before `file10`
+    Natural,
+    /// Name ignoring case
+    IgnoreCase,
+    /// Most recently modified first
+    Mtime,
+    /// Largest first
+    Size,
+    /// Extension, then name
+    Extension,
+}
+
+impl SortKey {
+    pub fn name(self) -> &'static str {
+        match self {
+            SortKey::Name => "name",
+            SortKey::Natural => "natural",
+            SortKey::IgnoreCase => "ignore-case",
+            SortKey::Mtime => "mtime",
+            SortKey::Size => "size",
+            SortKey::Extension => "extension",
+        }
+    }
 
-    for entry in entries {
-        files.push(entry?);
+    /// The key after this one, wrapping around; used to cycle at runtime.
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Name => SortKey::Natural,
+            SortKey::Natural => SortKey::IgnoreCase,
+            SortKey::IgnoreCase => SortKey::Mtime,
+            SortKey::Mtime => SortKey::Size,
+            SortKey::Size => SortKey::Extension,
+            SortKey::Extension => SortKey::Name,
+        }
     }
+}
 
-    files.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+pub fn list_directory<P: AsRef<Path>>(
+    path: P,
+    options: &ListOptions,
+) -> Result<Vec<Entry>, ExplorerError> {
+    // An entry that cannot be read is left out rather than failing the whole
+    // listing; the `Loader` reports them instead.
+    let mut files: Vec<Entry> = open_dir(path.as_ref())?
+        .filter_map(|entry| read_entry(entry, options).ok().flatten())
+        .collect();
+
+    files.sort_by(|a, b| options.sort.compare(a, b));
     Ok(files)
 }
 
+pub(crate) fn open_dir(path: &Path) -> Result<ReadDir, ExplorerError> {
+    fs::read_dir(path).map_err(|err| match err.kind() {
+        io::ErrorKind::PermissionDenied => {
+            ExplorerError::PermissionDenied(path.to_string_lossy().to_string())
+        }
+        _ => ExplorerError::Io(err),
+    })
+}
+
+/// Reads one entry of a directory listing. Dotfiles that are not wanted and
+/// entries removed since the directory was read give `Ok(None)`; other errors
+/// name the entry they are about.
+pub(crate) fn read_entry(
+    entry: io::Result<DirEntry>,
+    options: &ListOptions,
+) -> io::Result<Option<Entry>> {
+    let entry = entry?;
+    if !options.show_hidden && is_hidden(&entry.file_name()) {
+        return Ok(None);
+    }
+    match Entry::from_dir_entry(&entry) {
+        Ok(entry) => Ok(Some(entry)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(io::Error::new(
+            err.kind(),
+            format!("{}: {}", entry.path().display(), err),
+        )),
+    }
+}
+
+pub(crate) fn is_hidden(name: &OsStr) -> bool {
+    name.as_encoded_bytes().starts_with(b".")
+}
+
+impl SortOrder {
+    pub fn compare(&self, a: &Entry, b: &Entry) -> Ordering {
+        let by_key = match self.key {
+            SortKey::Name => Ordering::Equal,
+            SortKey::Natural => natural_cmp(&a.name, &b.name),
+            SortKey::IgnoreCase => ignore_case_cmp(&a.name, &b.name),
+            SortKey::Mtime => b.modified.cmp(&a.modified),
+            SortKey::Size => b.size.cmp(&a.size),
+            SortKey::Extension => extension(&a.name).cmp(extension(&b.name)),
+        }
+        .then_with(|| a.name.cmp(&b.name));
+        let by_key = if self.reverse {
+            by_key.reverse()
+        } else {
+            by_key
+        };
+        if self.dirs_first {
+            b.is_dir().cmp(&a.is_dir()).then(by_key)
+        } else {
+            by_key
+        }
+    }
+}
+
+/// Compares names as their lowercase forms would, without building them;
+/// only names that are not valid UTF-8 are copied.
+fn ignore_case_cmp(a: &OsStr, b: &OsStr) -> Ordering {
+    let (a, b) = (a.to_string_lossy(), b.to_string_lossy());
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .cmp(b.chars().flat_map(char::to_lowercase))
+}
+
+fn extension(name: &OsStr) -> &[u8] {
+    let bytes = name.as_encoded_bytes();
+    match bytes.iter().rposition(|&b| b == b'.') {
+        Some(i) if i > 0 => &bytes[i + 1..],
+        _ => &[],
+    }
+}
+
+/// Compares names chunk by chunk, ordering runs of ASCII digits by their
+/// numeric value.
+fn natural_cmp(a: &OsStr, b: &OsStr) -> Ordering {
+    let (mut a, mut b) = (a.as_encoded_bytes(), b.as_encoded_bytes());
+    while !a.is_empty() && !b.is_empty() {
+        let ordering = if a[0].is_ascii_digit() && b[0].is_ascii_digit() {
+            let (a_digits, a_rest) = split_digits(a);
+            let (b_digits, b_rest) = split_digits(b);
+            a = a_rest;
+            b = b_rest;
+            let a_value = trim_leading_zeros(a_digits);
+            let b_value = trim_leading_zeros(b_digits);
+            a_value
+                .len()
+                .cmp(&b_value.len())
+                .then_with(|| a_value.cmp(b_value))
+        } else {
+            let ordering = a[0].cmp(&b[0]);
+            a = &a[1..];
+            b = &b[1..];
+            ordering
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+fn split_digits(bytes: &[u8]) -> (&[u8], &[u8]) {
+    let end = bytes
+        .iter()
+        .position(|b| !b.is_ascii_digit())
+        .unwrap_or(bytes.len());
+    bytes.split_at(end)
+}
+
+fn trim_leading_zeros(digits: &[u8]) -> &[u8] {
+    let start = digits
+        .iter()
+        .position(|&b| b != b'0')
+        .unwrap_or(digits.len());
+    &digits[start..]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -23,7 +208,7 @@ mod tests {
     #[test]
     fn test_list_empty_directory() {
         let temp_dir = TempDir::new().unwrap();
-        let result = list_directory(temp_dir.path());
+        let result = list_directory(temp_dir.path(), &ListOptions::default());
         assert!(result.is_ok());
         let entries = result.unwrap();
         assert_eq!(entries.len(), 0);
@@ -36,7 +221,7 @@ mod tests {
         fs::write(temp_dir.path().join("file2.txt"), "content").unwrap();
         fs::write(temp_dir.path().join("file3.txt"), "content").unwrap();
 
-        let entries = list_directory(temp_dir.path()).unwrap();
+        let entries = list_directory(temp_dir.path(), &ListOptions::default()).unwrap();
         assert_eq!(entries.len(), 3);
     }
 
@@ -46,7 +231,7 @@ mod tests {
         fs::create_dir(temp_dir.path().join("dir1")).unwrap();
         fs::create_dir(temp_dir.path().join("dir2")).unwrap();
 
-        let entries = list_directory(temp_dir.path()).unwrap();
+        let entries = list_directory(temp_dir.path(), &ListOptions::default()).unwrap();
         assert_eq!(entries.len(), 2);
     }
 
@@ -56,12 +241,12 @@ mod tests {
         fs::create_dir(temp_dir.path().join("subdir")).unwrap();
         fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
 
-        let entries = list_directory(temp_dir.path()).unwrap();
+        let entries = list_directory(temp_dir.path(), &ListOptions::default()).unwrap();
         assert_eq!(entries.len(), 2);
 
         let names: Vec<String> = entries
             .iter()
-            .map(|e| e.file_name().to_string_lossy().to_string())
+            .map(|e| e.name.to_string_lossy().to_string())
             .collect();
         assert!(names.contains(&"subdir".to_string()));
         assert!(names.contains(&"file.txt".to_string()));
@@ -75,10 +260,10 @@ mod tests {
         fs::write(temp_dir.path().join("banana.txt"), "content").unwrap();
         fs::write(temp_dir.path().join("cherry.txt"), "content").unwrap();
 
-        let entries = list_directory(temp_dir.path()).unwrap();
+        let entries = list_directory(temp_dir.path(), &ListOptions::default()).unwrap();
         let names: Vec<String> = entries
             .iter()
-            .map(|e| e.file_name().to_string_lossy().to_string())
+            .map(|e| e.name.to_string_lossy().to_string())
             .collect();
 
         assert_eq!(
@@ -94,33 +279,247 @@ mod tests {
         fs::write(temp_dir.path().join("apple.txt"), "content").unwrap();
         fs::write(temp_dir.path().join("Banana.txt"), "content").unwrap();
 
-        let entries = list_directory(temp_dir.path()).unwrap();
+        let entries = list_directory(temp_dir.path(), &ListOptions::default()).unwrap();
         let names: Vec<String> = entries
             .iter()
-            .map(|e| e.file_name().to_string_lossy().to_string())
+            .map(|e| e.name.to_string_lossy().to_string())
             .collect();
 
         assert_eq!(names, vec!["Banana.txt", "Zebra.txt", "apple.txt"]);
     }
 
+    fn sorted_names(temp_dir: &TempDir, sort: SortOrder) -> Vec<String> {
+        let options = ListOptions {
+            show_hidden: false,
+            sort,
+        };
+        list_directory(temp_dir.path(), &options)
+            .unwrap()
+            .iter()
+            .map(|e| e.name.to_string_lossy().to_string())
+            .collect()
+    }
+
+    fn sorted_by(key: SortKey) -> SortOrder {
+        SortOrder {
+            key,
+            ..SortOrder::default()
+        }
+    }
+
+    #[test]
+    fn test_list_directory_sorted_natural() {
+        let temp_dir = TempDir::new().unwrap();
+        for name in ["file10.txt", "file2.txt", "file1.txt", "file02b.txt"] {
+            fs::write(temp_dir.path().join(name), "content").unwrap();
+        }
+
+        assert_eq!(
+            sorted_names(&temp_dir, sorted_by(SortKey::Natural)),
+            vec!["file1.txt", "file2.txt", "file02b.txt", "file10.txt"]
+        );
+    }
+
+    #[test]
+    fn test_list_directory_sorted_ignore_case() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("Zebra.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("apple.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("Banana.txt"), "content").unwrap();
+
+        assert_eq!(
+            sorted_names(&temp_dir, sorted_by(SortKey::IgnoreCase)),
+            vec!["apple.txt", "Banana.txt", "Zebra.txt"]
+        );
+    }
+
+    #[test]
+    fn test_ignore_case_cmp() {
+        let cmp = |a: &str, b: &str| ignore_case_cmp(OsStr::new(a), OsStr::new(b));
+        assert_eq!(cmp("README", "readme"), Ordering::Equal);
+        assert_eq!(cmp("abc", "ABCd"), Ordering::Less);
+        assert_eq!(cmp("Zebra", "apple"), Ordering::Greater);
+        assert_eq!(cmp("Éclair", "éclair"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_list_directory_sorted_by_mtime() {
+        let temp_dir = TempDir::new().unwrap();
+        let now = std::time::SystemTime::now();
+        for (name, age) in [("old.txt", 300), ("new.txt", 0), ("mid.txt", 100)] {
+            let file = fs::File::create(temp_dir.path().join(name)).unwrap();
+            file.set_modified(now - std::time::Duration::from_secs(age))
+                .unwrap();
+        }
+
+        assert_eq!(
+            sorted_names(&temp_dir, sorted_by(SortKey::Mtime)),
+            vec!["new.txt", "mid.txt", "old.txt"]
+        );
+    }
+
+    #[test]
+    fn test_list_directory_sorted_by_size() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("small.txt"), "a").unwrap();
+        fs::write(temp_dir.path().join("large.txt"), "aaaaaaaaaa").unwrap();
+        fs::write(temp_dir.path().join("medium.txt"), "aaaaa").unwrap();
+
+        assert_eq!(
+            sorted_names(&temp_dir, sorted_by(SortKey::Size)),
+            vec!["large.txt", "medium.txt", "small.txt"]
+        );
+    }
+
+    #[test]
+    fn test_list_directory_sorted_by_extension() {
+        let temp_dir = TempDir::new().unwrap();
+        for name in ["b.rs", "a.toml", "c.md", "Makefile", "a.rs"] {
+            fs::write(temp_dir.path().join(name), "content").unwrap();
+        }
+
+        assert_eq!(
+            sorted_names(&temp_dir, sorted_by(SortKey::Extension)),
+            vec!["Makefile", "c.md", "a.rs", "b.rs", "a.toml"]
+        );
+    }
+
+    #[test]
+    fn test_list_directory_sorted_reversed() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("apple.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("banana.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("cherry.txt"), "content").unwrap();
+
+        let sort = SortOrder {
+            reverse: true,
+            ..SortOrder::default()
+        };
+        assert_eq!(
+            sorted_names(&temp_dir, sort),
+            vec!["cherry.txt", "banana.txt", "apple.txt"]
+        );
+    }
+
+    #[test]
+    fn test_list_directory_sorted_dirs_first() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.txt"), "content").unwrap();
+        fs::create_dir(temp_dir.path().join("b_dir")).unwrap();
+        fs::write(temp_dir.path().join("c.txt"), "content").unwrap();
+        fs::create_dir(temp_dir.path().join("d_dir")).unwrap();
+
+        let sort = SortOrder {
+            dirs_first: true,
+            ..SortOrder::default()
+        };
+        assert_eq!(
+            sorted_names(&temp_dir, sort),
+            vec!["b_dir", "d_dir", "a.txt", "c.txt"]
+        );
+
+        let sort = SortOrder {
+            dirs_first: true,
+            reverse: true,
+            ..SortOrder::default()
+        };
+        assert_eq!(
+            sorted_names(&temp_dir, sort),
+            vec!["d_dir", "b_dir", "c.txt", "a.txt"]
+        );
+    }
+
+    #[test]
+    fn test_sort_key_cycles_through_every_key() {
+        let mut key = SortKey::default();
+        let mut seen = Vec::new();
+        loop {
+            seen.push(key);
+            key = key.next();
+            if key == SortKey::default() {
+                break;
+            }
+        }
+        assert_eq!(seen.len(), SortKey::value_variants().len());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_list_unreadable_directory_is_permission_denied() {
+        use std::os::unix::fs::PermissionsExt;
+        if uzers::get_effective_uid() == 0 {
+            // root can read anything
+            return;
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let locked = temp_dir.path().join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        let result = list_directory(&locked, &ListOptions::default());
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(matches!(result, Err(ExplorerError::PermissionDenied(_))));
+    }
+
+    #[test]
+    fn test_read_entry_skips_removed_entry() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("gone.txt"), "").unwrap();
+        let mut read_dir = fs::read_dir(temp_dir.path()).unwrap();
+        let entry = read_dir.next().unwrap();
+        fs::remove_file(temp_dir.path().join("gone.txt")).unwrap();
+
+        assert!(
+            read_entry(entry, &ListOptions::default())
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_read_entry_returns_other_errors() {
+        let entry = Err(io::Error::other("disk on fire"));
+        let err = read_entry(entry, &ListOptions::default()).unwrap_err();
+        assert_eq!(err.to_string(), "disk on fire");
+    }
+
     #[test]
     fn test_list_nonexistent_directory() {
-        let result = list_directory("/nonexistent/directory/path");
+        let result = list_directory("/nonexistent/directory/path", &ListOptions::default());
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_list_directory_skips_hidden_files_by_default() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join(".hidden"), "content").unwrap();
+        fs::create_dir(temp_dir.path().join(".config")).unwrap();
+        fs::write(temp_dir.path().join("visible.txt"), "content").unwrap();
+
+        let entries = list_directory(temp_dir.path(), &ListOptions::default()).unwrap();
+        let names: Vec<String> = entries
+            .iter()
+            .map(|e| e.name.to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["visible.txt"]);
+    }
+
     #[test]
     fn test_list_directory_with_hidden_files() {
         let temp_dir = TempDir::new().unwrap();
         fs::write(temp_dir.path().join(".hidden"), "content").unwrap();
         fs::write(temp_dir.path().join("visible.txt"), "content").unwrap();
 
-        let entries = list_directory(temp_dir.path()).unwrap();
+        let options = ListOptions {
+            show_hidden: true,
+            ..ListOptions::default()
+        };
+        let entries = list_directory(temp_dir.path(), &options).unwrap();
         assert_eq!(entries.len(), 2);
 
         let names: Vec<String> = entries
             .iter()
-            .map(|e| e.file_name().to_string_lossy().to_string())
+            .map(|e| e.name.to_string_lossy().to_string())
             .collect();
         assert!(names.contains(&".hidden".to_string()));
         assert!(names.contains(&"visible.txt".to_string()));
@@ -132,12 +531,12 @@ mod tests {
         fs::write(temp_dir.path().join("file with spaces.txt"), "content").unwrap();
         fs::write(temp_dir.path().join("file-with-dashes.txt"), "content").unwrap();
 
-        let entries = list_directory(temp_dir.path()).unwrap();
+        let entries = list_directory(temp_dir.path(), &ListOptions::default()).unwrap();
         assert_eq!(entries.len(), 2);
 
         let names: Vec<String> = entries
             .iter()
-            .map(|e| e.file_name().to_string_lossy().to_string())
+            .map(|e| e.name.to_string_lossy().to_string())
             .collect();
         assert!(names.contains(&"file with spaces.txt".to_string()));
         assert!(names.contains(&"file-with-dashes.txt".to_string()));
@@ -149,7 +548,7 @@ mod tests {
         fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
 
         let path_str = temp_dir.path().to_str().unwrap();
-        let entries = list_directory(path_str).unwrap();
+        let entries = list_directory(path_str, &ListOptions::default()).unwrap();
         assert_eq!(entries.len(), 1);
     }
 
@@ -159,7 +558,7 @@ mod tests {
         fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
 
         let path_buf = temp_dir.path().to_path_buf();
-        let entries = list_directory(path_buf).unwrap();
+        let entries = list_directory(path_buf, &ListOptions::default()).unwrap();
         assert_eq!(entries.len(), 1);
     }
 }
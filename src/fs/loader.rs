@@ -0,0 +1,301 @@
+//! Background listing of a single directory.
+//!
+//! [`list_directory`](super::list_directory) reads and stats every entry
+//! before returning, which freezes the UI on slow file systems and in huge
+//! directories. A [`Loader`] does the same work on its own thread and sends
+//! entries in batches, so they can be shown as they arrive and the load can be
+//! abandoned by dropping it.
+
+use super::cache::Stamp;
+use super::listing::{ListOptions, open_dir, read_entry};
+use super::metadata::Entry;
+use crate::error::ExplorerError;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a directory change waits for the listing before drawing what it
+/// has, so that fast directories appear in one go.
+pub const LOAD_WAIT: Duration = Duration::from_millis(30);
+
+/// Most entries sent in one batch.
+const BATCH_SIZE: usize = 512;
+
+/// Longest a read entry waits before it is sent, so slow directories fill in
+/// steadily.
+const BATCH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// What the loader thread sends besides a failure to read the directory.
+enum Update {
+    Entries(Vec<Entry>),
+    /// An entry that could not be read and was left out.
+    Skipped(String),
+}
+
+/// A running directory listing; dropping it stops the loader thread.
+pub struct Loader {
+    path: PathBuf,
+    options: ListOptions,
+    receiver: Receiver<Result<Update, ExplorerError>>,
+    cancel: Arc<AtomicBool>,
+    entries: Vec<Entry>,
+    opened: bool,
+    finished: bool,
+    failed: bool,
+    /// Entries left out since the last [`take_skipped`](Self::take_skipped).
+    skipped: Vec<String>,
+    /// The directory before it was read, for caching the listing.
+    stamp: Option<Stamp>,
+}
+
+impl Loader {
+    pub fn start(path: PathBuf, options: ListOptions) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&cancel);
+        let dir = path.clone();
+        thread::spawn(move || load(&dir, &options, &sender, &flag));
+        Self {
+            path,
+            options,
+            receiver,
+            cancel,
+            entries: Vec::new(),
+            opened: false,
+            finished: false,
+            failed: false,
+            skipped: Vec::new(),
+            stamp: None,
+        }
+    }
+
+    /// A load that is already done, for a listing known in advance.
+    pub fn cached(path: PathBuf, options: ListOptions, entries: Vec<Entry>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let _ = sender.send(Ok(Update::Entries(entries)));
+        Self {
+            path,
+            options,
+            receiver,
+            cancel: Arc::new(AtomicBool::new(false)),
+            entries: Vec::new(),
+            opened: false,
+            finished: false,
+            failed: false,
+            skipped: Vec::new(),
+            stamp: None,
+        }
+    }
+
+    pub fn with_stamp(mut self, stamp: Option<Stamp>) -> Self {
+        self.stamp = stamp;
+        self
+    }
+
+    pub fn stamp(&self) -> Option<Stamp> {
+        self.stamp
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn options(&self) -> ListOptions {
+        self.options
+    }
+
+    /// Takes in the entries read so far, waiting up to `timeout` for the load
+    /// to finish. Returns whether anything changed; a failed load returns the
+    /// error and is finished. New entries are appended, so the ones already
+    /// seen keep their place until the load finishes and sorts them.
+    pub fn poll(&mut self, timeout: Duration) -> Result<bool, ExplorerError> {
+        let deadline = Instant::now() + timeout;
+        let mut changed = false;
+        while !self.finished {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.receiver.recv_timeout(remaining) {
+                Ok(Ok(Update::Entries(batch))) => {
+                    self.opened = true;
+                    self.entries.extend(batch);
+                    changed = true;
+                }
+                Ok(Ok(Update::Skipped(reason))) => self.skipped.push(reason),
+                Ok(Err(err)) => {
+                    self.finished = true;
+                    self.failed = true;
+                    return Err(err);
+                }
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.finished = true;
+                    changed = true;
+                }
+            }
+        }
+        if changed && self.finished {
+            let sort = self.options.sort;
+            self.entries.sort_by(|a, b| sort.compare(a, b));
+        }
+        Ok(changed)
+    }
+
+    /// Why entries were left out of the listing since the last call, so the
+    /// rest of the directory still shows.
+    pub fn take_skipped(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.skipped)
+    }
+
+    /// Whether the directory could be opened, so entries are on their way.
+    pub fn is_opened(&self) -> bool {
+        self.opened
+    }
+
+    /// Whether the load is done, successfully or not.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Whether every entry has been read.
+    pub fn is_complete(&self) -> bool {
+        self.finished && !self.failed
+    }
+
+    /// The entries taken in so far: in the order they were read while the
+    /// load runs, then in the requested order.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+}
+
+impl Drop for Loader {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+fn load(
+    path: &Path,
+    options: &ListOptions,
+    sender: &Sender<Result<Update, ExplorerError>>,
+    cancel: &AtomicBool,
+) {
+    let read_dir = match open_dir(path) {
+        Ok(read_dir) => read_dir,
+        Err(err) => {
+            let _ = sender.send(Err(err));
+            return;
+        }
+    };
+    // An empty first batch tells the receiver the directory opened.
+    if sender.send(Ok(Update::Entries(Vec::new()))).is_err() {
+        return;
+    }
+    let mut batch = Vec::new();
+    let mut sent = Instant::now();
+    for entry in read_dir {
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        let entry = match read_entry(entry, options) {
+            Ok(Some(entry)) => entry,
+            Ok(None) => continue,
+            Err(err) => {
+                if sender.send(Ok(Update::Skipped(err.to_string()))).is_err() {
+                    return;
+                }
+                continue;
+            }
+        };
+        batch.push(entry);
+        if batch.len() >= BATCH_SIZE || sent.elapsed() >= BATCH_INTERVAL {
+            if sender
+                .send(Ok(Update::Entries(std::mem::take(&mut batch))))
+                .is_err()
+            {
+                return;
+            }
+            sent = Instant::now();
+        }
+    }
+    if !batch.is_empty() {
+        let _ = sender.send(Ok(Update::Entries(batch)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::{SortKey, SortOrder, list_directory};
+    use std::fs;
+    use tempfile::TempDir;
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+    fn names(loader: &Loader) -> Vec<String> {
+        loader
+            .entries()
+            .iter()
+            .map(|e| e.name.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_loader_lists_sorted_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        for name in ["b.txt", "a.txt", ".hidden", "c"] {
+            fs::write(temp_dir.path().join(name), "").unwrap();
+        }
+        let mut loader = Loader::start(temp_dir.path().to_path_buf(), ListOptions::default());
+        assert!(loader.poll(WAIT).unwrap());
+        assert!(loader.is_opened());
+        assert!(loader.is_finished());
+        assert_eq!(names(&loader), vec!["a.txt", "b.txt", "c"]);
+        assert!(!loader.poll(WAIT).unwrap());
+    }
+
+    #[test]
+    fn test_loader_honours_options() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("small"), "1").unwrap();
+        fs::write(temp_dir.path().join(".large"), "12345").unwrap();
+        let options = ListOptions {
+            show_hidden: true,
+            sort: SortOrder {
+                key: SortKey::Size,
+                ..SortOrder::default()
+            },
+        };
+        let mut loader = Loader::start(temp_dir.path().to_path_buf(), options);
+        loader.poll(WAIT).unwrap();
+        assert_eq!(names(&loader), vec![".large", "small"]);
+    }
+
+    #[test]
+    fn test_loader_reports_missing_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut loader = Loader::start(temp_dir.path().join("missing"), ListOptions::default());
+        assert!(loader.poll(WAIT).is_err());
+        assert!(!loader.is_opened());
+        assert!(loader.is_finished());
+        assert!(!loader.is_complete());
+    }
+
+    #[test]
+    fn test_cached_loader_is_done_at_once() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("b"), "").unwrap();
+        fs::write(temp_dir.path().join("a"), "").unwrap();
+        let entries = list_directory(temp_dir.path(), &ListOptions::default()).unwrap();
+        let mut loader = Loader::cached(
+            temp_dir.path().to_path_buf(),
+            ListOptions::default(),
+            entries.into_iter().rev().collect(),
+        );
+        assert!(loader.poll(Duration::ZERO).unwrap());
+        assert!(loader.is_complete());
+        assert_eq!(names(&loader), vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,366 @@
+use std::ffi::OsString;
+use std::fs::{self, DirEntry, Metadata, Permissions};
+use std::io;
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectType {
+    File,
+    Directory,
+    /// A symlink whose target exists; `to_dir` if that target is a directory.
+    Symlink {
+        to_dir: bool,
+    },
+    /// A symlink whose target does not exist.
+    BrokenSymlink,
+}
+
+impl ObjectType {
+    /// The stable name used in machine-readable output.
+    pub fn name(self) -> &'static str {
+        match self {
+            ObjectType::File => "file",
+            ObjectType::Directory => "directory",
+            ObjectType::Symlink { .. } => "symlink",
+            ObjectType::BrokenSymlink => "broken-symlink",
+        }
+    }
+
+    /// Whether breeze can enter it: a directory or a symlink to one.
+    pub fn is_dir(self) -> bool {
+        matches!(
+            self,
+            ObjectType::Directory | ObjectType::Symlink { to_dir: true }
+        )
+    }
+}
+
+/// What an entry is, without following symlinks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    File,
+    Directory,
+    Symlink,
+    /// Sockets, FIFOs, device nodes and the like.
+    Other,
+}
+
+impl From<fs::FileType> for FileType {
+    fn from(file_type: fs::FileType) -> Self {
+        if file_type.is_symlink() {
+            FileType::Symlink
+        } else if file_type.is_dir() {
+            FileType::Directory
+        } else if file_type.is_file() {
+            FileType::File
+        } else {
+            FileType::Other
+        }
+    }
+}
+
+/// Where a symlink points.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkTarget {
+    /// The link contents as stored, which may be relative to the link.
+    pub path: PathBuf,
+    /// Type of the file the link resolves to, or `None` if it is broken.
+    pub file_type: Option<FileType>,
+}
+
+/// A directory entry with its metadata.
+///
+/// Everything but the symlink target comes from a single `lstat`; symlinks
+/// additionally cost a `readlink` and a `stat` of the target.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub name: OsString,
+    pub path: PathBuf,
+    pub file_type: FileType,
+    pub symlink_target: Option<LinkTarget>,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    pub permissions: Permissions,
+    /// Owning user and group ids; `None` on platforms without them.
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+}
+
+impl Entry {
+    pub fn from_path(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let metadata = fs::symlink_metadata(&path)?;
+        let name = path
+            .file_name()
+            .map_or_else(|| path.as_os_str().to_owned(), |name| name.to_owned());
+        Ok(Self::with_metadata(name, path, &metadata))
+    }
+
+    pub fn from_dir_entry(entry: &DirEntry) -> io::Result<Self> {
+        // `DirEntry::metadata` does not follow symlinks.
+        let metadata = entry.metadata()?;
+        Ok(Self::with_metadata(
+            entry.file_name(),
+            entry.path(),
+            &metadata,
+        ))
+    }
+
+    fn with_metadata(name: OsString, path: PathBuf, metadata: &Metadata) -> Self {
+        let file_type = FileType::from(metadata.file_type());
+        let symlink_target = match file_type {
+            FileType::Symlink => Some(read_link_target(&path)),
+            _ => None,
+        };
+        #[cfg(unix)]
+        let (uid, gid) = {
+            use std::os::unix::fs::MetadataExt;
+            (Some(metadata.uid()), Some(metadata.gid()))
+        };
+        #[cfg(not(unix))]
+        let (uid, gid) = (None, None);
+        Entry {
+            name,
+            path,
+            file_type,
+            symlink_target,
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+            permissions: metadata.permissions(),
+            uid,
+            gid,
+        }
+    }
+
+    /// Whether the entry is a directory or a symlink to one.
+    pub fn is_dir(&self) -> bool {
+        match &self.symlink_target {
+            Some(target) => target.file_type == Some(FileType::Directory),
+            None => self.file_type == FileType::Directory,
+        }
+    }
+
+    /// Whether the current user may read the entry, and for a directory
+    /// also enter it, judged from its permission bits. Symlinks are judged
+    /// by their target when it is opened, so they always count as readable.
+    pub fn is_readable(&self) -> bool {
+        #[cfg(unix)]
+        {
+            let (uid, groups) = process_identity();
+            self.readable_by(uid, groups)
+        }
+        #[cfg(not(unix))]
+        {
+            true
+        }
+    }
+
+    #[cfg(unix)]
+    fn readable_by(&self, uid: u32, groups: &[u32]) -> bool {
+        use std::os::unix::fs::PermissionsExt;
+        let (Some(owner), Some(group)) = (self.uid, self.gid) else {
+            return true;
+        };
+        if uid == 0 || self.file_type == FileType::Symlink {
+            return true;
+        }
+        let mode = self.permissions.mode();
+        let bits = if owner == uid {
+            mode >> 6
+        } else if groups.contains(&group) {
+            mode >> 3
+        } else {
+            mode
+        } & 0o7;
+        let needed = match self.file_type {
+            FileType::Directory => 0o5,
+            _ => 0o4,
+        };
+        bits & needed == needed
+    }
+
+    pub fn kind(&self) -> ObjectType {
+        match (&self.symlink_target, self.file_type) {
+            (
+                Some(LinkTarget {
+                    file_type: None, ..
+                }),
+                _,
+            ) => ObjectType::BrokenSymlink,
+            (Some(_), _) => ObjectType::Symlink {
+                to_dir: self.is_dir(),
+            },
+            (None, FileType::Directory) => ObjectType::Directory,
+            (None, _) => ObjectType::File,
+        }
+    }
+}
+
+/// Effective uid and group ids of this process, looked up once.
+#[cfg(unix)]
+fn process_identity() -> (u32, &'static [u32]) {
+    static GROUPS: OnceLock<Vec<u32>> = OnceLock::new();
+    let groups = GROUPS.get_or_init(|| {
+        let mut groups: Vec<u32> = uzers::group_access_list()
+            .map(|groups| groups.iter().map(|g| g.gid()).collect())
+            .unwrap_or_default();
+        groups.push(uzers::get_effective_gid());
+        groups
+    });
+    (uzers::get_effective_uid(), groups)
+}
+
+fn read_link_target(path: &Path) -> LinkTarget {
+    LinkTarget {
+        path: fs::read_link(path).unwrap_or_default(),
+        file_type: fs::metadata(path)
+            .ok()
+            .map(|metadata| FileType::from(metadata.file_type())),
+    }
+}
+
+impl From<PathBuf> for ObjectType {
+    fn from(path_buf: PathBuf) -> Self {
+        Entry::from_path(path_buf).map_or(ObjectType::File, |entry| entry.kind())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_object_type_from_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let path_buf = temp_dir.path().to_path_buf();
+        let obj_type = ObjectType::from(path_buf);
+        assert_eq!(obj_type, ObjectType::Directory);
+    }
+
+    #[test]
+    fn test_object_type_from_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.txt");
+        fs::write(&file_path, "content").unwrap();
+        let obj_type = ObjectType::from(file_path);
+        assert_eq!(obj_type, ObjectType::File);
+    }
+
+    #[test]
+    fn test_entry_from_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.txt");
+        fs::write(&file_path, "content").unwrap();
+
+        let entry = Entry::from_path(&file_path).unwrap();
+        assert_eq!(entry.name, "test.txt");
+        assert_eq!(entry.path, file_path);
+        assert_eq!(entry.file_type, FileType::File);
+        assert_eq!(entry.size, 7);
+        assert!(entry.modified.is_some());
+        assert!(entry.symlink_target.is_none());
+        assert_eq!(entry.kind(), ObjectType::File);
+    }
+
+    #[test]
+    fn test_entry_from_dir_entry() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("subdir")).unwrap();
+
+        let dir_entry = fs::read_dir(temp_dir.path())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        let entry = Entry::from_dir_entry(&dir_entry).unwrap();
+        assert_eq!(entry.name, "subdir");
+        assert_eq!(entry.file_type, FileType::Directory);
+        assert!(entry.is_dir());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_entry_from_symlinks() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("target")).unwrap();
+        std::os::unix::fs::symlink("target", temp_dir.path().join("link")).unwrap();
+        std::os::unix::fs::symlink("missing", temp_dir.path().join("broken")).unwrap();
+
+        let link = Entry::from_path(temp_dir.path().join("link")).unwrap();
+        assert_eq!(link.file_type, FileType::Symlink);
+        assert_eq!(
+            link.symlink_target,
+            Some(LinkTarget {
+                path: PathBuf::from("target"),
+                file_type: Some(FileType::Directory),
+            })
+        );
+        assert!(link.is_dir());
+
+        assert_eq!(link.kind(), ObjectType::Symlink { to_dir: true });
+
+        let broken = Entry::from_path(temp_dir.path().join("broken")).unwrap();
+        assert_eq!(broken.file_type, FileType::Symlink);
+        assert_eq!(broken.kind(), ObjectType::BrokenSymlink);
+        assert_eq!(broken.symlink_target.unwrap().file_type, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_object_type_from_symlinks() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+        std::os::unix::fs::symlink("file.txt", temp_dir.path().join("link")).unwrap();
+        std::os::unix::fs::symlink("missing", temp_dir.path().join("broken")).unwrap();
+
+        let link = ObjectType::from(temp_dir.path().join("link"));
+        assert_eq!(link, ObjectType::Symlink { to_dir: false });
+        assert!(!link.is_dir());
+        assert_eq!(
+            ObjectType::from(temp_dir.path().join("broken")),
+            ObjectType::BrokenSymlink
+        );
+        assert!(ObjectType::Symlink { to_dir: true }.is_dir());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_entry_readable_by_permission_bits() {
+        use std::os::unix::fs::PermissionsExt;
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("dir");
+        fs::create_dir(&dir).unwrap();
+
+        let mut entry = Entry::from_path(&dir).unwrap();
+        entry.uid = Some(1000);
+        entry.gid = Some(100);
+
+        entry.permissions = fs::Permissions::from_mode(0o700);
+        assert!(entry.readable_by(1000, &[]));
+        assert!(!entry.readable_by(1001, &[100]));
+        assert!(entry.readable_by(0, &[]));
+
+        entry.permissions = fs::Permissions::from_mode(0o750);
+        assert!(entry.readable_by(1001, &[100]));
+        assert!(!entry.readable_by(1001, &[200]));
+
+        // Listing a directory needs both read and execute.
+        entry.permissions = fs::Permissions::from_mode(0o744);
+        assert!(!entry.readable_by(1001, &[200]));
+        entry.file_type = FileType::File;
+        assert!(entry.readable_by(1001, &[200]));
+    }
+
+    #[test]
+    fn test_object_type_names() {
+        assert_eq!(ObjectType::File.name(), "file");
+        assert_eq!(ObjectType::Directory.name(), "directory");
+        assert_eq!(ObjectType::Symlink { to_dir: true }.name(), "symlink");
+        assert_eq!(ObjectType::BrokenSymlink.name(), "broken-symlink");
+    }
+}
@@ -1,3 +1,11 @@
+pub mod cache;
 pub mod listing;
+pub mod loader;
+pub mod metadata;
+pub mod traversal;
 
-pub use listing::list_directory;
+pub use cache::{ListingCache, Stamp};
+pub use listing::{ListOptions, SortKey, SortOrder, list_directory};
+pub use loader::Loader;
+pub use metadata::{Entry, FileType, LinkTarget, ObjectType};
+pub use traversal::{Found, Walk, WalkOptions};
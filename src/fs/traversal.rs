@@ -0,0 +1,238 @@
+//! Background traversal of a directory subtree.
+//!
+//! A [`Walk`] reads the tree breadth-first on its own thread, so shallow paths
+//! arrive first and the UI stays responsive in large trees. Paths are sent in
+//! one batch per directory and are relative to the root. Symlinked
+//! directories are reported but not descended into, which keeps link cycles
+//! out of the walk.
+
+use super::listing::is_hidden;
+use super::metadata::ObjectType;
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+
+pub const DEFAULT_MAX_DEPTH: usize = 10;
+
+/// Directory names skipped by default: version control and build output.
+pub const DEFAULT_EXCLUDE: [&str; 3] = [".git", "target", "node_modules"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkOptions {
+    /// Deepest level reported; 1 is the root's own entries.
+    pub max_depth: usize,
+    /// Entry names that are neither reported nor descended into.
+    pub exclude: Vec<OsString>,
+    pub show_hidden: bool,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            exclude: DEFAULT_EXCLUDE.iter().map(OsString::from).collect(),
+            show_hidden: false,
+        }
+    }
+}
+
+/// A path found below the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Found {
+    pub path: PathBuf,
+    pub kind: ObjectType,
+}
+
+/// A running traversal; dropping it stops the walker thread.
+pub struct Walk {
+    receiver: Receiver<Vec<Found>>,
+    cancel: Arc<AtomicBool>,
+    finished: bool,
+}
+
+impl Walk {
+    pub fn start(root: PathBuf, options: WalkOptions) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&cancel);
+        thread::spawn(move || walk(&root, &options, &sender, &flag));
+        Self {
+            receiver,
+            cancel,
+            finished: false,
+        }
+    }
+
+    /// Paths found since the last call, without blocking.
+    pub fn try_next(&mut self) -> Vec<Found> {
+        let mut found = Vec::new();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(batch) => found.extend(batch),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.finished = true;
+                    break;
+                }
+            }
+        }
+        found
+    }
+
+    /// Whether the walker is done and everything it found has been taken.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+impl Drop for Walk {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+fn walk(root: &Path, options: &WalkOptions, sender: &Sender<Vec<Found>>, cancel: &AtomicBool) {
+    let mut queue = VecDeque::from([(PathBuf::new(), 1)]);
+    while let Some((dir, depth)) = queue.pop_front() {
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        // Unreadable directories are skipped rather than ending the walk.
+        let Ok(read_dir) = fs::read_dir(root.join(&dir)) else {
+            continue;
+        };
+        let mut batch = Vec::new();
+        for entry in read_dir.flatten() {
+            let name = entry.file_name();
+            if options.exclude.contains(&name) || (!options.show_hidden && is_hidden(&name)) {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = dir.join(&name);
+            let kind = if file_type.is_symlink() {
+                ObjectType::from(entry.path())
+            } else if file_type.is_dir() {
+                ObjectType::Directory
+            } else {
+                ObjectType::File
+            };
+            if file_type.is_dir() && depth < options.max_depth {
+                queue.push_back((path.clone(), depth + 1));
+            }
+            batch.push(Found { path, kind });
+        }
+        if !batch.is_empty() && sender.send(batch).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn walk_all(root: &Path, options: WalkOptions) -> Vec<PathBuf> {
+        let mut walk = Walk::start(root.to_path_buf(), options);
+        let mut paths = Vec::new();
+        while !walk.is_finished() {
+            paths.extend(walk.try_next().into_iter().map(|found| found.path));
+            thread::yield_now();
+        }
+        paths.sort();
+        paths
+    }
+
+    fn tree() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/deep/deeper")).unwrap();
+        fs::create_dir_all(root.join(".git/objects")).unwrap();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join("src/deep/deeper/leaf.rs"), "").unwrap();
+        fs::write(root.join(".env"), "").unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_walk_skips_excluded_and_hidden() {
+        let temp_dir = tree();
+        let paths = walk_all(temp_dir.path(), WalkOptions::default());
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("src"),
+                PathBuf::from("src/deep"),
+                PathBuf::from("src/deep/deeper"),
+                PathBuf::from("src/deep/deeper/leaf.rs"),
+                PathBuf::from("src/main.rs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_walk_depth_limit() {
+        let temp_dir = tree();
+        let options = WalkOptions {
+            max_depth: 2,
+            ..WalkOptions::default()
+        };
+        let paths = walk_all(temp_dir.path(), options);
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("src"),
+                PathBuf::from("src/deep"),
+                PathBuf::from("src/main.rs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_walk_hidden_entries_when_shown() {
+        let temp_dir = tree();
+        let options = WalkOptions {
+            max_depth: 1,
+            exclude: Vec::new(),
+            show_hidden: true,
+        };
+        let paths = walk_all(temp_dir.path(), options);
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from(".env"),
+                PathBuf::from(".git"),
+                PathBuf::from("src"),
+                PathBuf::from("target"),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_does_not_follow_symlinked_directories() {
+        let temp_dir = tree();
+        std::os::unix::fs::symlink(temp_dir.path(), temp_dir.path().join("src/loop")).unwrap();
+        let mut walk = Walk::start(temp_dir.path().to_path_buf(), WalkOptions::default());
+        let mut found = Vec::new();
+        while !walk.is_finished() {
+            found.extend(walk.try_next());
+            thread::yield_now();
+        }
+        let link = found
+            .iter()
+            .find(|f| f.path == Path::new("src/loop"))
+            .unwrap();
+        assert_eq!(link.kind, ObjectType::Symlink { to_dir: true });
+        let below_link = found.iter().filter(|f| f.path.starts_with("src/loop"));
+        assert_eq!(below_link.count(), 1);
+    }
+}
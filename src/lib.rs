@@ -1,7 +1,9 @@
 pub mod cli;
 pub mod core;
 pub mod error;
+pub mod filter;
 pub mod fs;
 
-pub use core::explorer::Explorer;
+pub use core::explorer::{Explorer, PathMode};
 pub use error::ExplorerError;
+pub use fs::Entry;
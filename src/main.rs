@@ -1,154 +1,238 @@
-mod cli;
-mod core;
-mod error;
-mod fs;
-
-use anyhow::{Context, Result};
-use clap::Parser;
-use cli::args::Args;
-use core::explorer::Explorer;
-use crossterm::{
-    ExecutableCommand,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
-};
-use error::ExplorerError;
-use nucleo_matcher::{
-    Config, Matcher, Utf32Str,
-    pattern::{CaseMatching, Normalization, Pattern},
+use anyhow::Result;
+use breeze_cli::cli::args::{Args, Commands};
+use breeze_cli::cli::columns::Details;
+use breeze_cli::cli::input::read_paths;
+use breeze_cli::cli::output::{Command, Item, Outcome, Output};
+use breeze_cli::cli::shell::init_script;
+use breeze_cli::cli::terminal::{
+    SIGNAL_POLL_INTERVAL, Screen, Signals, TerminalGuard, install_panic_hook,
 };
+use breeze_cli::core::explorer::Explorer;
+use breeze_cli::core::messages::{Messages, Severity};
+use breeze_cli::core::modes::{Action, Mode};
+use breeze_cli::core::navigation::AutoCd;
+use breeze_cli::core::selection::Selection;
+use breeze_cli::core::session::Session;
+use breeze_cli::error::ExplorerError;
+use breeze_cli::filter::fuzzy::{self, Fuzzy};
+use breeze_cli::filter::search::{self, MAX_HITS, Search};
+use breeze_cli::fs::loader::{self, Loader};
+use breeze_cli::fs::{Entry, ListOptions, ObjectType, WalkOptions};
+use clap::Parser;
 use ratatui::{
-    Terminal,
+    Terminal, TerminalOptions, Viewport,
     backend::CrosstermBackend,
     buffer::Buffer,
-    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
-    layout::{Constraint, Layout, Rect},
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+        terminal,
+    },
+    layout::{Constraint, Layout, Position, Rect},
     style::{
         Color, Modifier, Style, Stylize,
-        palette::tailwind::{BLUE, SLATE},
+        palette::tailwind::{AMBER, BLUE, GREEN, RED, SLATE},
     },
     symbols,
-    text::Line,
+    text::{Line, Span},
     widgets::{
-        Block, Borders, HighlightSpacing, List, ListItem, ListState, Paragraph, StatefulWidget,
-        Widget,
+        Block, Borders, Clear, HighlightSpacing, List, ListItem, ListState, Paragraph,
+        StatefulWidget, Widget,
     },
 };
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::{BufWriter, IsTerminal, Stderr, Write, stderr, stdin, stdout};
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
-use std::{
-    io::{BufWriter, IsTerminal, Read, Stderr, stderr, stdin},
-};
-use std::{fmt, fs::DirEntry};
+use std::process::ExitCode;
+use std::time::{Duration, Instant, SystemTime};
 
 const HEADER_STYLE: Style = Style::new().fg(SLATE.c100).bg(BLUE.c800);
 const NORMAL_ROW_BG: Color = SLATE.c950;
 const SELECTED_STYLE: Style = Style::new().bg(SLATE.c800);
 const TEXT_FG_COLOR: Color = SLATE.c200;
+const MARKED_STYLE: Style = Style::new().fg(GREEN.c400);
+const DETAIL_STYLE: Style = Style::new().fg(SLATE.c400);
+const BROKEN_LINK_STYLE: Style = Style::new().fg(RED.c400);
+const UNREADABLE_STYLE: Style = Style::new().fg(SLATE.c500).add_modifier(Modifier::DIM);
+const STATUS_INFO_STYLE: Style = Style::new().fg(BLUE.c300).bg(NORMAL_ROW_BG);
+const STATUS_WARN_STYLE: Style = Style::new().fg(AMBER.c400).bg(NORMAL_ROW_BG);
+const STATUS_ERROR_STYLE: Style = Style::new().fg(RED.c400).bg(NORMAL_ROW_BG);
+
+fn main() -> ExitCode {
+    match try_main() {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("breeze: {}", err);
+            Outcome::Error.into()
+        }
+    }
+}
+
+fn try_main() -> Result<ExitCode, ExplorerError> {
+    let args = Args::parse();
+
+    if let Some(Commands::Init { shell, cmd }) = &args.command {
+        print!("{}", init_script(*shell, cmd));
+        return Ok(ExitCode::SUCCESS);
+    }
 
-fn main() -> Result<(), ExplorerError> {
-    let args = if stdin().is_terminal() {
-        Args::parse()
+    // Picker mode: choose among paths piped in; keys come from /dev/tty.
+    let candidates = if stdin().is_terminal() {
+        None
     } else {
-        let mut buffer = String::new();
-        buffer.push_str("breeze-cli ");
-        let _ = stdin().read_to_string(&mut buffer)?;
-        Args::parse_from(buffer.trim().split_whitespace())
+        Some(read_paths(stdin().lock(), args.input_separator())?)
     };
 
-    let explorer = Explorer::new(args.directory.canonicalize()?)?;
-    let cwd = explorer.cwd();
-    let paths = explorer.ls()?;
+    let session = match &args.resume {
+        Some(path) => Session::load(path)?.filter(|session| session.cwd.is_dir()),
+        None => None,
+    };
+    let directory = session
+        .as_ref()
+        .map_or(&args.directory, |session| &session.cwd);
+
+    let explorer = Explorer::with_path_mode(directory.clone(), args.path_mode())?
+        .with_options(args.list_options())
+        .with_cache_capacity(args.cache_capacity());
     let handle = stderr();
 
-    let backend = CrosstermBackend::new(BufWriter::new(&handle));
-    let terminal = Terminal::new(backend)?;
-    let mut app = App {
-        handle: &handle,
-        should_exit: false,
-        path_list: PathList::from_iter(paths),
-        explorer: explorer,
-        output: Output::new(cwd),
-        matcher: Matcher::new(Config::DEFAULT.match_paths()),
-        pattern: None,
-        filter_string: String::new(),
+    install_panic_hook();
+    let screen = args.screen();
+    let viewport = match screen {
+        Screen::Fullscreen => Viewport::Fullscreen,
+        Screen::Inline(height) => Viewport::Inline(height.rows(terminal::size()?.1)),
     };
-    let result = app.run(terminal);
-    println!("{}", app.output);
-    result
+    let backend = CrosstermBackend::new(BufWriter::new(&handle));
+    let terminal = Terminal::with_options(backend, TerminalOptions { viewport })?;
+    let mut app = App::new(&handle, explorer)?;
+    match candidates {
+        Some(paths) => app.set_candidates(paths),
+        None => app.auto_cd = args.auto_cd(),
+    }
+    app.walk_options = args.walk_options();
+    app.details = args.details();
+    app.long_listing = args.long;
+    app.signals = Signals::register()?;
+    if let Some(session) = &session {
+        app.restore(session);
+    }
+    let outcome = app.run(terminal, screen)?;
+    if app.messages.dropped_errors() > 0 {
+        eprintln!(
+            "breeze: {} earlier errors not shown",
+            app.messages.dropped_errors()
+        );
+    }
+    for message in app.messages.errors() {
+        eprintln!("breeze: {}", message.text);
+    }
+    if let Some(path) = &args.cwd_file {
+        app.output.write_cwd(&mut File::create(path)?)?;
+    }
+    if let Some(path) = &args.selection_file {
+        app.output
+            .write_selection(&mut File::create(path)?, args.output_format())?;
+    }
+    if !args.writes_result_to_files() {
+        let mut stdout = stdout().lock();
+        app.output.write(&mut stdout, args.output_format())?;
+        stdout.flush()?;
+    }
+    // The result is already out: a session that cannot be saved only costs
+    // the next iteration its state, so it is not an error.
+    if let Some(path) = &args.resume
+        && let Err(err) = app.session().save(path)
+    {
+        eprintln!("breeze: warning: could not save session: {}", err);
+    }
+    Ok(outcome.into())
 }
 
 struct App<'a> {
     handle: &'a Stderr,
     should_exit: bool,
+    /// The cursor, and the results while searching; the directory listing
+    /// itself is read from `fuzzy` a screenful at a time.
     path_list: PathList,
     explorer: Explorer,
     output: Output,
-    matcher: Matcher,
-    pattern: Option<Pattern>,
+    /// Every listed path, matched against the filter in the background.
+    fuzzy: Fuzzy<Path>,
     filter_string: String,
+    mode: Mode,
+    selection: Selection,
+    visual_anchor: Option<usize>,
+    auto_cd: Option<AutoCd>,
+    details: Details,
+    long_listing: bool,
+    messages: Messages,
+    /// Whether the last key press failed, so leaving with it is an error.
+    last_key_failed: bool,
+    /// Whether the message log overlay is open.
+    show_log: bool,
+    signals: Signals,
+    /// Whether paths read from stdin are listed instead of the current
+    /// directory.
+    picker: bool,
+    /// Recursive search below the current directory, listed instead of it.
+    search: Option<Search>,
+    walk_options: WalkOptions,
+    loading: Option<Loading>,
 }
 
-struct Output {
-    cwd: String,
-    command: String,
-    items: Vec<String>,
+/// A directory listing still arriving in the background.
+struct Loading {
+    loader: Loader,
+    /// Entry to highlight once it is listed.
+    select: Option<OsString>,
+    /// How many of the loaded entries are listed already.
+    shown: usize,
 }
 
-impl Output {
-    fn new(cwd: String) -> Self {
-        Output {
-            cwd,
-            command: "no-op".to_string(),
-            items: Vec::new(),
+impl Loading {
+    fn new(loader: Loader, select: Option<OsString>) -> Self {
+        Self {
+            loader,
+            select,
+            shown: 0,
         }
     }
 }
 
-impl fmt::Display for Output {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} {} {}", self.cwd, self.command, self.items.join(" "))
-    }
-}
-
+#[derive(Default)]
 struct PathList {
     items: Vec<Path>,
     state: ListState,
 }
 
+#[derive(Clone)]
 struct Path {
     value: String,
+    raw: OsString,
     kind: ObjectType,
     match_indices: Vec<u32>,
+    /// Metadata for the detail columns, when the path came from a listing.
+    entry: Option<Entry>,
 }
 
 impl Path {
-    fn new(value: String, kind: ObjectType) -> Self {
-        Self {
-            value,
-            kind,
-            match_indices: Vec::new(),
-        }
+    fn new(raw: impl Into<OsString>, kind: ObjectType) -> Self {
+        Self::with_match_indices(raw, kind, Vec::new())
     }
 
-    fn with_match_indices(value: String, kind: ObjectType, match_indices: Vec<u32>) -> Self {
+    fn with_match_indices(
+        raw: impl Into<OsString>,
+        kind: ObjectType,
+        match_indices: Vec<u32>,
+    ) -> Self {
+        let raw = raw.into();
         Self {
-            value,
+            value: raw.to_string_lossy().to_string(),
+            raw,
             kind,
             match_indices,
-        }
-    }
-}
-
-#[derive(Clone, Copy)]
-enum ObjectType {
-    File,
-    Directory,
-}
-
-impl From<PathBuf> for ObjectType {
-    fn from(path_buf: PathBuf) -> Self {
-        match path_buf.is_dir() {
-            true => ObjectType::Directory,
-            false => ObjectType::File,
+            entry: None,
         }
     }
 }
@@ -157,105 +241,607 @@ impl FromIterator<PathBuf> for PathList {
     fn from_iter<I: IntoIterator<Item = PathBuf>>(iter: I) -> Self {
         let items = iter
             .into_iter()
-            .map(|pb| Path::new(pb.to_string_lossy().to_string(), ObjectType::from(pb)))
+            .map(|pb| Path::new(pb.clone(), ObjectType::from(pb)))
             .collect();
         let state = ListState::default();
         Self { items, state }
     }
 }
 
-impl FromIterator<DirEntry> for PathList {
-    fn from_iter<I: IntoIterator<Item = DirEntry>>(iter: I) -> Self {
-        let items = iter
-            .into_iter()
-            .map(|de| {
-                Path::new(
-                    de.file_name().to_string_lossy().to_string(),
-                    ObjectType::from(de.path()),
-                )
-            })
-            .collect();
+impl From<Entry> for Path {
+    fn from(entry: Entry) -> Self {
+        let mut path = Path::new(entry.name.clone(), entry.kind());
+        path.entry = Some(entry);
+        path
+    }
+}
+
+impl FromIterator<Entry> for PathList {
+    fn from_iter<I: IntoIterator<Item = Entry>>(iter: I) -> Self {
+        let items = iter.into_iter().map(Path::from).collect();
         let state = ListState::default();
         Self { items, state }
     }
 }
 
-impl App<'_> {
-    fn run(
+impl<'a> App<'a> {
+    fn new(handle: &'a Stderr, explorer: Explorer) -> Result<Self, ExplorerError> {
+        let mut app = App {
+            handle,
+            should_exit: false,
+            path_list: PathList::default(),
+            output: Output::new(explorer.current_dir.clone()),
+            explorer,
+            fuzzy: Fuzzy::new(),
+            filter_string: String::new(),
+            mode: Mode::default(),
+            selection: Selection::new(),
+            visual_anchor: None,
+            auto_cd: None,
+            details: Details::default(),
+            long_listing: false,
+            messages: Messages::new(),
+            last_key_failed: false,
+            show_log: false,
+            signals: Signals::default(),
+            picker: false,
+            search: None,
+            walk_options: WalkOptions::default(),
+            loading: None,
+        };
+        let loader = app.explorer.start_ls();
+        app.start_load(loader, None)?;
+        // Nothing is highlighted until the first key press.
+        app.path_list.state.select(None);
+        Ok(app)
+    }
+
+    /// Switches to picker mode, listing `paths` instead of the current
+    /// directory.
+    fn set_candidates(&mut self, paths: Vec<PathBuf>) {
+        self.picker = true;
+        self.loading = None;
+        self.load(PathList::from_iter(paths).items);
+        self.path_list.state.select_first();
+    }
+
+    /// Replaces the listed paths, applying the current filter to them. The
+    /// cursor is reset.
+    fn load(&mut self, paths: impl IntoIterator<Item = Path>) {
+        self.fuzzy.clear();
+        for path in paths {
+            let text = path.value.clone();
+            self.fuzzy.push(path, &text);
+        }
+        self.fuzzy.tick(fuzzy::MATCH_WAIT_MS);
+        self.path_list.state = ListState::default();
+    }
+
+    /// Starts taking in the listing from `loader`, replacing any load in
+    /// progress, and waits briefly for it.
+    fn start_load(
         &mut self,
-        mut terminal: Terminal<CrosstermBackend<BufWriter<&Stderr>>>,
+        loader: Loader,
+        select: Option<OsString>,
     ) -> Result<(), ExplorerError> {
-        let mut unhandled = Vec::new();
+        self.loading = Some(Loading::new(loader, select));
+        self.poll_load(loader::LOAD_WAIT)
+    }
+
+    /// Lists the entries loaded so far, waiting up to `timeout` for more. The
+    /// explorer moves to a new directory once it has been opened; if it
+    /// cannot be, the error is returned and the current listing stays.
+    fn poll_load(&mut self, timeout: Duration) -> Result<(), ExplorerError> {
+        let Some(mut loading) = self.loading.take() else {
+            return Ok(());
+        };
+        let was_opened = loading.loader.is_opened();
+        let changed = loading.loader.poll(timeout)?;
+        let skipped = loading.loader.take_skipped();
+        match skipped.as_slice() {
+            [] => {}
+            [reason] => self.messages.warn(format!("Skipped {}", reason)),
+            [reason, rest @ ..] => self.messages.warn(format!(
+                "Skipped {} and {} more entries",
+                reason,
+                rest.len()
+            )),
+        }
+        if !changed || !loading.loader.is_opened() {
+            if !loading.loader.is_finished() {
+                self.loading = Some(loading);
+            }
+            return Ok(());
+        }
+        if !was_opened {
+            self.explorer.enter(&loading.loader);
+        }
+
+        // Entries are listed in the order they arrive, and the listing is
+        // replaced once by the sorted one when the last of them is in.
+        let selected = self.selected_raw().filter(|_| was_opened);
+        let entries = loading.loader.entries();
+        let restart = !was_opened || (loading.loader.is_finished() && loading.shown > 0);
+        let new = if restart { 0 } else { loading.shown };
+        if restart {
+            self.load(entries.iter().cloned().map(Path::from));
+        } else {
+            for entry in &entries[new..] {
+                let path = Path::from(entry.clone());
+                let text = path.value.clone();
+                self.fuzzy.push(path, &text);
+            }
+        }
+        loading.shown = entries.len();
+
+        let wanted = loading
+            .select
+            .take_if(|name| entries[new..].iter().any(|entry| entry.name == *name));
+        if wanted.is_some() && !restart {
+            self.fuzzy.tick(fuzzy::MATCH_WAIT_MS);
+        }
+        if let Some(i) = wanted.and_then(|name| self.position(&name)) {
+            self.path_list.state.select(Some(i));
+        } else if restart && was_opened {
+            self.reselect(selected);
+        } else if restart {
+            self.path_list.state.select_first();
+        }
+
+        if loading.loader.is_finished() {
+            self.explorer.remember(&loading.loader);
+        } else {
+            self.loading = Some(loading);
+        }
+        Ok(())
+    }
+
+    /// Abandons the listing in progress. A directory that has not been
+    /// opened yet is not entered; one that has keeps what is listed so far.
+    fn cancel_load(&mut self) {
+        let Some(loading) = self.loading.take() else {
+            return;
+        };
+        let path = loading.loader.path().display();
+        if loading.loader.is_opened() {
+            self.messages.warn(format!(
+                "Stopped listing {} after {} entries",
+                path,
+                loading.loader.entries().len()
+            ));
+        } else {
+            self.messages.info(format!("Cancelled opening {}", path));
+        }
+    }
+
+    /// Number of listed paths: the search results while searching, otherwise
+    /// the entries the filter matches so far.
+    fn path_count(&self) -> usize {
+        match &self.search {
+            Some(_) => self.path_list.items.len(),
+            None => self.fuzzy.count(),
+        }
+    }
+
+    /// The listed path at `i`.
+    fn path(&self, i: usize) -> Option<&Path> {
+        match &self.search {
+            Some(_) => self.path_list.items.get(i),
+            None => self.fuzzy.get(i),
+        }
+    }
+
+    /// Characters of the listed path at `i` that the filter matched.
+    fn match_indices(&mut self, i: usize) -> Vec<u32> {
+        match &self.search {
+            Some(_) => self
+                .path_list
+                .items
+                .get(i)
+                .map_or_else(Vec::new, |path| path.match_indices.clone()),
+            None => self.fuzzy.indices(i),
+        }
+    }
 
-        enable_raw_mode()?;
-        self.handle.execute(EnterAlternateScreen)?;
+    fn selected_path(&self) -> Option<&Path> {
+        self.selected_index().and_then(|i| self.path(i))
+    }
+
+    fn selected_raw(&self) -> Option<OsString> {
+        self.selected_path().map(|path| path.raw.clone())
+    }
+
+    /// Index of the listed path named `raw`.
+    fn position(&self, raw: &OsStr) -> Option<usize> {
+        // Usually the highlighted path, which then needs no search.
+        if let Some(i) = self.selected_index()
+            && self.path(i).is_some_and(|path| path.raw == raw)
+        {
+            return Some(i);
+        }
+        (0..self.path_count()).find(|&i| self.path(i).is_some_and(|path| path.raw == raw))
+    }
+
+    /// Highlights the path named `raw` again after the list changed, or the
+    /// first path if it is gone.
+    fn reselect(&mut self, raw: Option<OsString>) {
+        if let Some(raw) = raw {
+            let position = self.position(&raw);
+            self.path_list.state.select(position.or(Some(0)));
+        }
+    }
+
+    fn run(
+        &mut self,
+        mut terminal: Terminal<CrosstermBackend<BufWriter<&Stderr>>>,
+        screen: Screen,
+    ) -> Result<Outcome, ExplorerError> {
+        let mut guard = TerminalGuard::enter(self.handle, screen)?;
+        let mut redraw = true;
         while !self.should_exit {
-            terminal.draw(|frame| frame.render_widget(&mut *self, frame.area()))?;
+            if self.signals.terminate_requested() {
+                self.terminate();
+                break;
+            }
+            if self.signals.take_suspend() {
+                // Clearing first lets the shell prompt take over the rows of
+                // an inline viewport; resizing afterwards places it again
+                // below wherever the cursor is on resume.
+                terminal.clear()?;
+                terminal.backend_mut().flush()?;
+                guard.suspend()?;
+                terminal.resize(Rect::from((Position::ORIGIN, terminal.size()?)))?;
+                redraw = true;
+            }
+            if redraw {
+                terminal.draw(|frame| frame.render_widget(&mut *self, frame.area()))?;
+            }
+            // Wake up regularly to notice signals, even without a timer.
+            let timer = self.timeout();
+            let timeout = timer.map_or(SIGNAL_POLL_INTERVAL, |t| t.min(SIGNAL_POLL_INTERVAL));
+            if !event::poll(timeout)? {
+                self.tick();
+                redraw = timer.is_some_and(|t| t <= SIGNAL_POLL_INTERVAL);
+                continue;
+            }
+            redraw = true;
             if let Event::Key(key) = event::read()? {
-                match self.handle_key(key) {
-                    Ok(_) => (),
-                    err => {
-                        let i = self.path_list.state.selected();
-                        let selected = i
-                            .map(|idx| self.path_list.items[idx].value.to_string())
-                            .unwrap_or("nothing".to_string());
-                        let msg = format!(
-                            "Failed on key {:?} with {:?} selected",
-                            key.code.to_string(),
-                            selected
-                        );
-                        unhandled.push(err.context(msg))
-                    }
+                let result = self.handle_key(key);
+                self.last_key_failed = result.is_err();
+                if let Err(err) = result {
+                    self.report(err);
                 }
-            };
+            }
         }
 
-        self.handle.execute(LeaveAlternateScreen)?;
-        disable_raw_mode()?;
+        // Erases an inline viewport; the alternate screen goes away on its own.
+        terminal.clear()?;
+        terminal.backend_mut().flush()?;
+        drop(guard);
+        self.output.cwd = self.explorer.current_dir.clone();
+        Ok(self.outcome())
+    }
 
-        if unhandled.len() > 0 {
-            println!("{:?}", unhandled);
+    /// How the run ended. A result the user chose stands even if something
+    /// failed on the way there; errors only count when nothing was chosen,
+    /// or when the key press that ended the run failed.
+    fn outcome(&self) -> Outcome {
+        let outcome = self.output.outcome();
+        if self.last_key_failed || (outcome == Outcome::Cancelled && self.messages.has_errors()) {
+            return Outcome::Error;
+        }
+        outcome
+    }
+
+    /// Time until something changes without a key press: a pending auto-cd,
+    /// the status line expiring, a running search finding more paths, a
+    /// directory still loading or matching still in progress.
+    fn timeout(&self) -> Option<Duration> {
+        let now = Instant::now();
+        let searching = self.fuzzy.is_running()
+            || self.loading.is_some()
+            || self
+                .search
+                .as_ref()
+                .is_some_and(|s| !s.is_finished() || s.is_matching());
+        [
+            self.auto_cd.as_ref().and_then(|a| a.timeout(now)),
+            self.messages.timeout(now),
+            searching.then_some(search::REFRESH_INTERVAL),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+
+    /// Handles a poll timeout: runs a pending auto-cd once it is due and
+    /// takes in newly loaded entries, search results and matches.
+    fn tick(&mut self) {
+        let settling = self.loading.is_some() || self.fuzzy.is_running();
+        let due = self
+            .auto_cd
+            .as_mut()
+            .is_some_and(|auto_cd| auto_cd.take_due(Instant::now()));
+        if due && let Err(err) = self.auto_enter_directory() {
+            self.report(err);
+        }
+        if let Err(err) = self.poll_load(Duration::ZERO) {
+            self.report(err);
+        }
+        if let Some(search) = &mut self.search {
+            let found = search.poll();
+            if search.tick(0) || found {
+                self.update_search(true);
+            }
+        }
+        if self.fuzzy.is_running() {
+            let selected = self.selected_raw();
+            if self.fuzzy.tick(0) && self.search.is_none() {
+                self.reselect(selected);
+            }
+        }
+        // The filter may only now have settled on a single directory.
+        if settling && let Err(err) = self.arm_auto_cd() {
+            self.report(err);
+        }
+    }
+
+    /// Shows a failed action in the status line. Permission problems are
+    /// expected while browsing and only warn.
+    fn report(&mut self, err: ExplorerError) {
+        match err {
+            ExplorerError::PermissionDenied(_) => self.messages.warn(err.to_string()),
+            err => {
+                let selected = self
+                    .selected_path()
+                    .map_or("nothing".to_string(), |path| path.value.clone());
+                self.messages
+                    .error(format!("{} (with {:?} selected)", err, selected));
+            }
         }
-        Ok(())
     }
 
     fn handle_key(&mut self, key: KeyEvent) -> Result<(), ExplorerError> {
         if key.kind != KeyEventKind::Press {
             return Ok(());
         }
-        match key.code {
-            KeyCode::Char('Q') => self.should_exit = true,
-            KeyCode::Esc => self.clear_filter(),
-            KeyCode::Down => self.select_next(),
-            KeyCode::Up => self.select_previous(),
-            KeyCode::Home => self.select_first(),
-            KeyCode::End => self.select_last(),
-            KeyCode::Right => {
+        if self.show_log {
+            if matches!(
+                key.code,
+                KeyCode::Esc | KeyCode::Char('m') | KeyCode::Char('q')
+            ) {
+                self.show_log = false;
+            }
+            return Ok(());
+        }
+        if self.loading.is_some() && key.code == KeyCode::Esc {
+            self.cancel_load();
+            return Ok(());
+        }
+        match self.mode.action(key) {
+            Some(action) => self.perform(action),
+            None => Ok(()),
+        }
+    }
+
+    fn perform(&mut self, action: Action) -> Result<(), ExplorerError> {
+        if let Some(auto_cd) = &mut self.auto_cd {
+            auto_cd.cancel();
+        }
+        if self.picker && action.browses_directory() {
+            return Ok(());
+        }
+        if self.mode == Mode::Visual
+            && matches!(
+                action,
+                Action::EnterDirectory | Action::ChangeToParent | Action::Run(_)
+            )
+        {
+            self.end_visual();
+        }
+        if self.search.is_some() {
+            match action {
+                Action::PushFilter(c) => {
+                    self.filter_string.push(c);
+                    self.update_search(false);
+                    return Ok(());
+                }
+                Action::PopFilter => {
+                    self.filter_string.pop();
+                    self.update_search(false);
+                    return Ok(());
+                }
+                Action::NewFilter => {
+                    self.filter_string.clear();
+                    self.update_search(false);
+                    self.mode = Mode::Insert;
+                    return Ok(());
+                }
+                Action::Escape | Action::ClearFilter => {
+                    self.end_search();
+                    return Ok(());
+                }
+                Action::EnterDirectory | Action::Run(Command::Select) => {
+                    return self.jump_to_hit();
+                }
+                ref action if action.browses_directory() => return Ok(()),
+                _ => (),
+            }
+        }
+        match action {
+            Action::Quit => self.quit(),
+            Action::Escape if self.filter_string.is_empty() && self.picker => self.quit(),
+            Action::Escape | Action::ClearFilter => self.clear_filter(),
+            Action::ChangeDirectoryAndExit => self.change_directory_and_exit(),
+            Action::PushFilter(c) => {
+                self.filter_paths(c);
+                self.arm_auto_cd()?;
+            }
+            Action::PopFilter => self.remove_last_char_from_filter(),
+            Action::SelectNext => self.select_next(),
+            Action::SelectPrevious => self.select_previous(),
+            Action::SelectFirst => self.select_first(),
+            Action::SelectLast => self.select_last(),
+            Action::EnterDirectory => {
                 self.enter_directory()?;
                 self.clear_filter();
             }
-            KeyCode::Left => {
+            Action::ChangeToParent => {
                 self.change_to_parent()?;
                 self.clear_filter();
             }
-            KeyCode::Enter => self.update_command("do-thing".to_string(), true),
-            KeyCode::Char(c) => self.filter_paths(c),
-            KeyCode::Backspace => self.remove_last_char_from_filter(),
-            _ => (),
+            Action::Run(command) => self.update_command(command, true),
+            Action::SwitchMode(mode) => self.mode = mode,
+            Action::NewFilter => {
+                self.clear_filter();
+                self.mode = Mode::Insert;
+            }
+            Action::ToggleMark => self.toggle_mark(),
+            Action::MarkAll => {
+                let entries: Vec<_> = (0..self.path_count())
+                    .filter_map(|i| self.entry(i))
+                    .collect();
+                self.selection.extend(entries);
+            }
+            Action::InvertMarks => {
+                let entries: Vec<_> = (0..self.path_count())
+                    .filter_map(|i| self.entry(i))
+                    .collect();
+                self.selection.invert(entries);
+            }
+            Action::ClearMarks => self.selection.clear(),
+            Action::StartVisual => self.start_visual(),
+            Action::EndVisual => self.end_visual(),
+            Action::ToggleHidden => {
+                self.toggle_hidden()?;
+                let state = if self.explorer.options.show_hidden {
+                    "shown"
+                } else {
+                    "hidden"
+                };
+                self.messages.info(format!("Hidden files {}", state));
+            }
+            Action::ToggleLongListing => self.long_listing = !self.long_listing,
+            Action::FollowLink => self.follow_link()?,
+            Action::CycleSort => {
+                let sort = &mut self.explorer.options.sort;
+                sort.key = sort.key.next();
+                let name = sort.key.name();
+                self.reload()?;
+                self.messages.info(format!("Sorted by {}", name));
+            }
+            Action::ReverseSort => {
+                let sort = &mut self.explorer.options.sort;
+                sort.reverse = !sort.reverse;
+                let order = if sort.reverse { "reversed" } else { "normal" };
+                self.reload()?;
+                self.messages.info(format!("Sort order {}", order));
+            }
+            Action::ShowMessages => self.show_log = true,
+            Action::Suspend => self.signals.request_suspend(),
+            Action::StartSearch => self.start_search(),
+            Action::Refresh => {
+                self.refresh()?;
+                self.messages.info("Listing refreshed");
+            }
+        }
+        Ok(())
+    }
+
+    /// Arms auto-cd if the filter leaves a single directory, and cancels it
+    /// otherwise. Matches that may still change, because the matcher or the
+    /// listing is not done, do not count; `tick` checks again once they are.
+    fn arm_auto_cd(&mut self) -> Result<(), ExplorerError> {
+        let settled =
+            !self.filter_string.is_empty() && !self.fuzzy.is_running() && self.loading.is_none();
+        // Whether there is a single match only depends on the first two.
+        let kinds: Vec<_> = (0..self.path_count().min(2))
+            .filter_map(|i| self.path(i))
+            .map(|path| path.kind)
+            .collect();
+        let Some(auto_cd) = &mut self.auto_cd else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        if settled {
+            auto_cd.update(kinds, now);
+        } else {
+            auto_cd.cancel();
+        }
+        if auto_cd.take_due(now) {
+            self.auto_enter_directory()?;
+        }
+        Ok(())
+    }
+
+    /// Enters the single directory left by the filter once the auto-cd
+    /// delay has passed.
+    fn auto_enter_directory(&mut self) -> Result<(), ExplorerError> {
+        if let Some(auto_cd) = &mut self.auto_cd {
+            auto_cd.cancel();
         }
+        self.path_list.state.select_first();
+        self.enter_directory()?;
+        self.clear_filter();
         Ok(())
     }
 
+    /// Absolute path and kind of the entry at `i` in the visible list.
+    fn entry(&self, i: usize) -> Option<(PathBuf, ObjectType)> {
+        let path = self.path(i)?;
+        Some((self.explorer.current_dir.join(&path.raw), path.kind))
+    }
+
+    /// Index of the highlighted entry, clamped to the visible list.
+    fn selected_index(&self) -> Option<usize> {
+        let last = self.path_count().checked_sub(1)?;
+        self.path_list.state.selected().map(|i| i.min(last))
+    }
+
+    fn toggle_mark(&mut self) {
+        if let Some((path, kind)) = self.selected_index().and_then(|i| self.entry(i)) {
+            self.selection.toggle(path, kind);
+            self.select_next();
+        }
+    }
+
+    fn start_visual(&mut self) {
+        if let Some(i) = self.selected_index() {
+            self.visual_anchor = Some(i);
+            self.mode = Mode::Visual;
+        }
+    }
+
+    fn end_visual(&mut self) {
+        if let Some(range) = self.visual_range() {
+            let entries: Vec<_> = range.filter_map(|i| self.entry(i)).collect();
+            self.selection.extend(entries);
+        }
+        self.visual_anchor = None;
+        self.mode = Mode::Normal;
+    }
+
+    fn visual_range(&self) -> Option<RangeInclusive<usize>> {
+        let anchor = self.visual_anchor?;
+        let cursor = self.selected_index()?;
+        Some(anchor.min(cursor)..=anchor.max(cursor))
+    }
+
+    fn is_marked(&self, i: usize) -> bool {
+        self.visual_range().is_some_and(|range| range.contains(&i))
+            || self
+                .entry(i)
+                .is_some_and(|(path, _)| self.selection.contains(&path))
+    }
+
     fn clear_filter(&mut self) {
         self.filter_string.clear();
-        self.pattern = None;
-        if let Ok(new_paths) = self.explorer.ls() {
-            self.path_list = PathList::from_iter(new_paths);
-        }
+        self.fuzzy.set_query("");
         // Auto-select first item after clearing filter
         self.path_list.state.select_first();
     }
 
+    #[cfg(test)]
     fn select_none(&mut self) {
         self.path_list.state.select(None);
     }
@@ -276,16 +862,110 @@ impl App<'_> {
     }
 
     fn enter_directory(&mut self) -> Result<(), ExplorerError> {
-        if let Some(i) = self.path_list.state.selected() {
-            if let ObjectType::Directory = self.path_list.items[i].kind {
-                let full_path = self
-                    .explorer
-                    .current_dir
-                    .join(self.path_list.items[i].value.to_string());
-                let new_paths = self.explorer.cd(full_path.into())?;
-                self.path_list = PathList::from_iter(new_paths);
-            }
+        if let Some(path) = self.selected_path()
+            && path.kind.is_dir()
+        {
+            let full_path = self.explorer.current_dir.join(&path.raw);
+            let loader = self.explorer.start_cd(full_path)?;
+            self.start_load(loader, None)?;
+        }
+        Ok(())
+    }
+
+    /// Moves to where the highlighted symlink points: into the target if it
+    /// is a directory, otherwise to the target's directory with the target
+    /// highlighted.
+    fn follow_link(&mut self) -> Result<(), ExplorerError> {
+        let Some(path) = self.selected_path() else {
+            return Ok(());
+        };
+        if !matches!(
+            path.kind,
+            ObjectType::Symlink { .. } | ObjectType::BrokenSymlink
+        ) {
+            return Ok(());
+        }
+        let link = self.explorer.current_dir.join(&path.raw);
+        if path.kind == ObjectType::BrokenSymlink {
+            // A dangling link is nothing to fail over, there is just nowhere
+            // to go.
+            let target = std::fs::read_link(&link).unwrap_or_default();
+            let message = format!(
+                "{} points to {}, which does not exist",
+                path.value,
+                target.display()
+            );
+            self.messages.warn(message);
+            return Ok(());
+        }
+        self.jump_to(link.canonicalize()?)
+    }
+
+    /// Moves into `target` if it is a directory, otherwise to the directory
+    /// containing it with `target` highlighted.
+    fn jump_to(&mut self, target: PathBuf) -> Result<(), ExplorerError> {
+        let (directory, name) = if target.is_dir() {
+            (target, None)
+        } else {
+            let name = target.file_name().map(|n| n.to_owned());
+            let parent = target.parent().map_or(target.clone(), |p| p.to_path_buf());
+            (parent, name)
+        };
+
+        let loader = self.explorer.start_cd(directory)?;
+        self.filter_string.clear();
+        self.fuzzy.set_query("");
+        self.start_load(loader, name)
+    }
+
+    fn start_search(&mut self) {
+        let options = WalkOptions {
+            show_hidden: self.explorer.options.show_hidden,
+            ..self.walk_options.clone()
+        };
+        self.search = Some(Search::start(self.explorer.current_dir.clone(), options));
+        self.filter_string.clear();
+        self.mode = Mode::Insert;
+        self.update_search(false);
+    }
+
+    /// Re-ranks the search results for the filter. With `keep_cursor`, the
+    /// highlighted path stays highlighted while new results stream in.
+    fn update_search(&mut self, keep_cursor: bool) {
+        let selected = self.selected_raw().filter(|_| keep_cursor);
+        let Some(search) = &mut self.search else {
+            return;
+        };
+        search.set_query(&self.filter_string);
+        search.tick(fuzzy::MATCH_WAIT_MS);
+        self.path_list.items = search
+            .hits(MAX_HITS)
+            .into_iter()
+            .map(|hit| Path::with_match_indices(hit.path, hit.kind, hit.indices))
+            .collect();
+        let position = selected.and_then(|raw| self.position(&raw));
+        self.path_list.state.select(position.or(Some(0)));
+    }
+
+    fn end_search(&mut self) {
+        self.search = None;
+        self.path_list.items.clear();
+        self.clear_filter();
+    }
+
+    /// Leaves the search for the directory of the highlighted result.
+    fn jump_to_hit(&mut self) -> Result<(), ExplorerError> {
+        let Some(path) = self.selected_path() else {
+            return Ok(());
+        };
+        let target = self.explorer.current_dir.join(&path.raw);
+        // The directory's listing replaces the results as soon as it loads.
+        let search = self.search.take();
+        if let Err(err) = self.jump_to(target) {
+            self.search = search;
+            return Err(err);
         }
+        self.path_list.items.clear();
         Ok(())
     }
 
@@ -297,181 +977,340 @@ impl App<'_> {
             .parent()
             .unwrap_or(current.as_path())
             .to_path_buf();
-        let new_paths = self.explorer.cd(parent)?;
-        self.path_list = PathList::from_iter(new_paths);
-        Ok(())
+        let loader = self.explorer.start_cd(parent)?;
+        self.start_load(loader, None)
     }
 
     fn filter_paths(&mut self, c: char) {
-        // Append new character to filter string
         self.filter_string.push(c);
+        self.apply_filter();
+    }
 
-        // Rebuild pattern from complete filter string
-        let pattern = Pattern::parse(
-            &self.filter_string,
-            CaseMatching::Ignore,
-            Normalization::Smart,
-        );
-
-        // Get all current items with their types
-        let items_with_types: Vec<(String, ObjectType)> = self
-            .path_list
-            .items
-            .iter()
-            .map(|e| (e.value.to_string(), e.kind))
-            .collect();
-
-        // Match and collect indices for each item
-        let mut new_items = Vec::new();
-        for (value, kind) in items_with_types {
-            let mut indices = Vec::new();
-            let mut buf = Vec::new();
-            let haystack = Utf32Str::new(&value, &mut buf);
-            let score = pattern.indices(haystack, &mut self.matcher, &mut indices);
-            if score.is_some() {
-                new_items.push((value, kind, indices, score.unwrap()));
-            }
-        }
-
-        // Sort by score (higher is better)
-        new_items.sort_by(|a, b| b.3.cmp(&a.3));
-
-        // Update path list with match indices
-        self.path_list.items = new_items
-            .into_iter()
-            .map(|(value, kind, indices, _)| Path::with_match_indices(value, kind, indices))
-            .collect();
-
-        self.pattern = Some(pattern);
+    fn remove_last_char_from_filter(&mut self) {
+        self.filter_string.pop();
+        self.apply_filter();
+    }
 
+    /// Matches the listing against the filter, waiting briefly so that fast
+    /// matches show up with the key press; slower ones arrive on later ticks.
+    fn apply_filter(&mut self) {
+        self.fuzzy.set_query(&self.filter_string);
+        self.fuzzy.tick(fuzzy::MATCH_WAIT_MS);
         // Auto-select first item in filtered list
         self.path_list.state.select_first();
     }
 
-    fn remove_last_char_from_filter(&mut self) {
-        // Remove last character from filter string
-        self.filter_string.pop();
+    /// Re-reads the current directory, keeping the filter and, if it is
+    /// still listed, the highlighted entry.
+    fn reload(&mut self) -> Result<(), ExplorerError> {
+        let selected = self.selected_raw();
+        let loader = self.explorer.start_ls();
+        self.start_load(loader, selected)
+    }
 
-        if self.filter_string.is_empty() {
-            // No filter - restore full directory listing
-            if let Ok(new_paths) = self.explorer.ls() {
-                self.path_list = PathList::from_iter(new_paths);
-            }
-            self.pattern = None;
-        } else {
-            // Rebuild pattern from updated filter string
-            let pattern = Pattern::parse(
-                &self.filter_string,
-                CaseMatching::Ignore,
-                Normalization::Smart,
-            );
+    /// Like [`reload`](Self::reload), but reads the directory from disk even
+    /// if the cached listing looks current.
+    fn refresh(&mut self) -> Result<(), ExplorerError> {
+        let selected = self.selected_raw();
+        let loader = self.explorer.start_refresh();
+        self.start_load(loader, selected)
+    }
 
-            // Re-fetch full directory and filter with match indices
-            if let Ok(new_paths) = self.explorer.ls() {
-                self.path_list = PathList::from_iter(new_paths);
+    fn toggle_hidden(&mut self) -> Result<(), ExplorerError> {
+        self.explorer.options.show_hidden = !self.explorer.options.show_hidden;
+        self.reload()
+    }
 
-                // Get all current items with their types
-                let items_with_types: Vec<(String, ObjectType)> = self
-                    .path_list
-                    .items
-                    .iter()
-                    .map(|e| (e.value.to_string(), e.kind))
-                    .collect();
-
-                // Match and collect indices for each item
-                let mut new_items = Vec::new();
-                for (value, kind) in items_with_types {
-                    let mut indices = Vec::new();
-                    let mut buf = Vec::new();
-                    let haystack = Utf32Str::new(&value, &mut buf);
-                    let score = pattern.indices(haystack, &mut self.matcher, &mut indices);
-                    if score.is_some() {
-                        new_items.push((value, kind, indices, score.unwrap()));
-                    }
+    fn restore(&mut self, session: &Session) {
+        for c in session.filter.chars() {
+            self.filter_paths(c);
+        }
+        let Some(name) = &session.selected else {
+            return;
+        };
+        match &mut self.loading {
+            // The entry may not have been read yet.
+            Some(loading) => loading.select = Some(name.clone()),
+            None => {
+                if let Some(i) = self.position(name) {
+                    self.path_list.state.select(Some(i));
                 }
-
-                // Sort by score (higher is better)
-                new_items.sort_by(|a, b| b.3.cmp(&a.3));
-
-                // Update path list with match indices
-                self.path_list.items = new_items
-                    .into_iter()
-                    .map(|(value, kind, indices, _)| Path::with_match_indices(value, kind, indices))
-                    .collect();
             }
+        }
+    }
 
-            self.pattern = Some(pattern);
+    fn session(&self) -> Session {
+        Session {
+            cwd: self.explorer.current_dir.clone(),
+            filter: self.filter_string.clone(),
+            selected: self
+                .loading
+                .as_ref()
+                .and_then(|loading| loading.select.clone())
+                .or_else(|| self.selected_raw()),
         }
+    }
 
-        // Auto-select first item after backspace
-        self.path_list.state.select_first();
+    fn quit(&mut self) {
+        self.output.command = Command::Quit;
+        self.output.items.clear();
+        self.should_exit = true;
     }
 
-    fn update_command(&mut self, command: String, quit: bool) {
-        if let Some(i) = self.path_list.state.selected() {
-            self.output.command = command;
-            let cwd = self.explorer.cwd();
-            self.output.items = vec![self.path_list.items[i].value.to_string()]
-                .iter()
-                .map(|s| format!("{}/{}", cwd, s))
-                .collect();
-            if quit {
-                self.should_exit = true;
-            }
+    /// Leaves without choosing anything because breeze was told to stop
+    /// from outside, which the wrapper reads back as `no-op`.
+    fn terminate(&mut self) {
+        self.output.command = Command::Noop;
+        self.output.items.clear();
+        self.should_exit = true;
+    }
+
+    fn change_directory_and_exit(&mut self) {
+        self.output.command = Command::Cd;
+        self.output.items = vec![Item::new(
+            self.explorer.current_dir.clone(),
+            ObjectType::Directory,
+        )];
+        self.output.filter.clear();
+        self.should_exit = true;
+    }
+
+    fn update_command(&mut self, command: Command, quit: bool) {
+        let items = if !self.selection.is_empty() {
+            self.selection.items()
+        } else if let Some(path) = self.selected_path() {
+            vec![Item::new(
+                self.explorer.current_dir.join(&path.raw),
+                path.kind,
+            )]
+        } else {
+            return;
+        };
+        self.output.command = command;
+        self.output.items = items;
+        self.output.filter = self.filter_string.clone();
+        if quit {
+            self.should_exit = true;
         }
     }
 }
 
 impl Widget for &mut App<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let [header_area, main_area, footer_area] = Layout::vertical([
+        let now = Instant::now();
+        let status_height = u16::from(self.messages.current(now).is_some());
+        let [header_area, main_area, status_area, footer_area] = Layout::vertical([
             Constraint::Length(2),
             Constraint::Fill(1),
+            Constraint::Length(status_height),
             Constraint::Length(1),
         ])
         .areas(area);
 
-        App::render_header(header_area, buf);
-        App::render_footer(&self.filter_string, footer_area, buf);
+        if let Some(message) = self.messages.current(now) {
+            Paragraph::new(message.text.as_str())
+                .style(status_style(message.severity))
+                .render(status_area, buf);
+        }
+
+        App::render_header(&self.explorer.options, header_area, buf);
+        App::render_footer(
+            self.mode,
+            &self.filter_string,
+            self.search.is_some(),
+            self.selection.len(),
+            footer_area,
+            buf,
+        );
         self.render_list(main_area, buf);
+        if self.show_log {
+            self.render_log(main_area, buf);
+        }
     }
 }
 
 impl App<'_> {
-    fn render_header(area: Rect, buf: &mut Buffer) {
+    fn render_header(options: &ListOptions, area: Rect, buf: &mut Buffer) {
+        let [title_area, status_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(area);
         Paragraph::new("MVP Breeze TUI")
             .bold()
             .centered()
-            .render(area, buf);
+            .render(title_area, buf);
+        let status = format!(
+            "sort: {}{} | hidden: {} ",
+            options.sort.key.name(),
+            if options.sort.reverse { " (rev)" } else { "" },
+            if options.show_hidden { "shown" } else { "off" },
+        );
+        Paragraph::new(status)
+            .fg(SLATE.c400)
+            .right_aligned()
+            .render(status_area, buf);
     }
 
-    fn render_footer(filter_string: &str, area: Rect, buf: &mut Buffer) {
-        let footer_text = if filter_string.is_empty() {
-            "Use ↓↑ to move, ← to unselect, → to change status, g/G to go top/bottom.".to_string()
+    fn render_footer(
+        mode: Mode,
+        filter_string: &str,
+        searching: bool,
+        marked: usize,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let mut footer_text = if searching {
+            format!("Search: {} | Enter jump, ESC cancel", filter_string)
+        } else if filter_string.is_empty() {
+            mode.hint().to_string()
         } else {
             format!("Filter: {} | ESC to clear", filter_string)
         };
-        Paragraph::new(footer_text).centered().render(area, buf);
+        if marked > 0 {
+            footer_text = format!("{} marked | {}", marked, footer_text);
+        }
+        let indicator_style = match mode {
+            Mode::Insert => Style::new().fg(SLATE.c950).bg(GREEN.c400),
+            Mode::Normal => Style::new().fg(SLATE.c950).bg(BLUE.c400),
+            Mode::Visual => Style::new().fg(SLATE.c950).bg(AMBER.c400),
+        };
+        let label = format!(" {} ", mode.label());
+        let [indicator_area, text_area] =
+            Layout::horizontal([Constraint::Length(label.len() as u16), Constraint::Fill(1)])
+                .areas(area);
+        Paragraph::new(label)
+            .style(indicator_style)
+            .bold()
+            .render(indicator_area, buf);
+        Paragraph::new(footer_text)
+            .centered()
+            .render(text_area, buf);
+    }
+
+    /// Draws the message log over `area`, newest message at the bottom.
+    fn render_log(&self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(Line::raw(" Messages (ESC to close) "))
+            .border_style(HEADER_STYLE)
+            .bg(NORMAL_ROW_BG);
+        let visible = usize::from(block.inner(area).height);
+        let log = self.messages.log();
+        let items: Vec<ListItem> = log[log.len().saturating_sub(visible)..]
+            .iter()
+            .map(|message| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<6}", message.severity.label()),
+                        status_style(message.severity),
+                    ),
+                    Span::styled(message.text.as_str(), TEXT_FG_COLOR),
+                ])
+                .into()
+            })
+            .collect();
+        Clear.render(area, buf);
+        if items.is_empty() {
+            Paragraph::new("No messages")
+                .fg(SLATE.c400)
+                .block(block)
+                .render(area, buf);
+        } else {
+            Widget::render(List::new(items).block(block), area, buf);
+        }
     }
 
     fn render_list(&mut self, area: Rect, buf: &mut Buffer) {
+        let title = match (&self.search, &self.loading) {
+            (Some(search), _) => format!(
+                "{} | {} paths{}",
+                self.explorer.cwd(),
+                search.len(),
+                if search.is_finished() {
+                    ""
+                } else {
+                    ", searching..."
+                }
+            ),
+            (None, Some(loading)) if loading.loader.is_opened() => format!(
+                "{} | {} entries, loading... (ESC stops)",
+                self.explorer.cwd(),
+                loading.loader.entries().len()
+            ),
+            (None, Some(loading)) => format!(
+                "{} | opening {}... (ESC cancels)",
+                self.explorer.cwd(),
+                loading.loader.path().display()
+            ),
+            (None, None) => self.explorer.cwd(),
+        };
         let block = Block::new()
-            .title(Line::raw(self.explorer.cwd()))
+            .title(Line::raw(title))
             .borders(Borders::TOP)
             .border_set(symbols::border::EMPTY)
             .border_style(HEADER_STYLE)
             .bg(NORMAL_ROW_BG);
 
-        // Iterate through all elements in the `items` and stylize them.
-        let items: Vec<ListItem> = self
+        // Only the rows on screen are built, so a huge listing draws as fast
+        // as a small one. Scroll just enough to keep the cursor in view.
+        let height = usize::from(block.inner(area).height).max(1);
+        let count = self.path_count();
+        let selected = self.selected_index();
+        let mut offset = self
             .path_list
-            .items
-            .iter()
-            .enumerate()
-            .map(|(_, path_item)| ListItem::from(path_item).bg(NORMAL_ROW_BG))
-            .collect();
+            .state
+            .offset()
+            .min(count.saturating_sub(height));
+        if let Some(i) = selected {
+            offset = offset.clamp((i + 1).saturating_sub(height), i);
+        }
+        self.path_list.state.select(selected);
+        *self.path_list.state.offset_mut() = offset;
+        let window = offset..count.min(offset + height);
+
+        // Detail columns go in front of the name; the highlight symbol and the
+        // mark column take two cells.
+        let details = if self.long_listing {
+            self.details.rows(
+                window
+                    .clone()
+                    .map(|i| self.path(i).and_then(|p| p.entry.as_ref())),
+                usize::from(area.width).saturating_sub(2),
+                SystemTime::now(),
+            )
+        } else {
+            Vec::new()
+        };
 
-        // Create a List from all list items and highlight the currently selected one
+        let mut items = Vec::with_capacity(window.len());
+        for (row, i) in window.enumerate() {
+            let match_indices = self.match_indices(i);
+            let Some(path_item) = self.path(i) else {
+                break;
+            };
+            let mut line = build_highlighted_line(&path_item.value, &match_indices);
+            if path_item.kind == ObjectType::BrokenSymlink {
+                line = line.patch_style(BROKEN_LINK_STYLE);
+            } else if path_item.entry.as_ref().is_some_and(|e| !e.is_readable()) {
+                line = line.patch_style(UNREADABLE_STYLE);
+            }
+            if let Some(target) = path_item
+                .entry
+                .as_ref()
+                .and_then(|entry| entry.symlink_target.as_ref())
+            {
+                line.spans.push(Span::styled(
+                    format!(" -> {}", target.path.to_string_lossy()),
+                    DETAIL_STYLE,
+                ));
+            }
+            if let Some(detail) = details.get(row) {
+                line.spans
+                    .insert(0, Span::styled(detail.clone(), DETAIL_STYLE));
+            }
+            let marker = if self.is_marked(i) { "+" } else { " " };
+            line.spans.insert(0, Span::styled(marker, MARKED_STYLE));
+            items.push(ListItem::new(line).bg(NORMAL_ROW_BG));
+        }
+
+        // Create a List from the visible rows and highlight the currently selected one
         let list = List::new(items)
             .block(block)
             .highlight_style(SELECTED_STYLE)
@@ -480,7 +1319,16 @@ impl App<'_> {
 
         // We need to disambiguate this trait method as both `Widget` and `StatefulWidget` share the
         // same method name `render`.
-        StatefulWidget::render(list, area, buf, &mut self.path_list.state);
+        let mut state = ListState::default().with_selected(selected.map(|i| i - offset));
+        StatefulWidget::render(list, area, buf, &mut state);
+    }
+}
+
+fn status_style(severity: Severity) -> Style {
+    match severity {
+        Severity::Info => STATUS_INFO_STYLE,
+        Severity::Warn => STATUS_WARN_STYLE,
+        Severity::Error => STATUS_ERROR_STYLE,
     }
 }
 
@@ -545,313 +1393,1302 @@ fn build_highlighted_line(value: &str, match_indices: &[u32]) -> Line<'static> {
     Line::from(spans)
 }
 
-impl From<&Path> for ListItem<'_> {
-    fn from(path: &Path) -> Self {
-        let line = build_highlighted_line(&path.value, &path.match_indices);
-        ListItem::new(line)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ratatui::crossterm::event::KeyModifiers;
     use std::fs;
     use tempfile::TempDir;
 
-    #[test]
-    fn test_output_new() {
-        let output = Output::new("/test/path".to_string());
-        assert_eq!(output.cwd, "/test/path");
-        assert_eq!(output.command, "no-op");
-        assert_eq!(output.items.len(), 0);
+    /// Every listed path's display name, in order.
+    fn values(app: &App) -> Vec<String> {
+        (0..app.path_count())
+            .map(|i| app.path(i).unwrap().value.clone())
+            .collect()
+    }
+
+    /// Ticks `app` until the listing has loaded and the filter has matched
+    /// it, or the search has walked and ranked everything, as the event loop
+    /// would.
+    fn settle(app: &mut App) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while app.loading.is_some()
+            || app.fuzzy.is_running()
+            || (app.search.as_ref()).is_some_and(|s| !s.is_finished() || s.is_matching())
+        {
+            assert!(Instant::now() < deadline, "app did not settle");
+            std::thread::sleep(Duration::from_millis(1));
+            app.tick();
+        }
+    }
+
+    #[test]
+    fn test_path_new() {
+        let path = Path::new("test.txt".to_string(), ObjectType::File);
+        assert_eq!(path.value, "test.txt");
+        matches!(path.kind, ObjectType::File);
+    }
+
+    #[test]
+    fn test_pathlist_from_iter_pathbufs() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("file1.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("file2.txt"), "content").unwrap();
+        fs::create_dir(temp_dir.path().join("subdir")).unwrap();
+
+        let paths: Vec<PathBuf> = vec![
+            temp_dir.path().join("file1.txt"),
+            temp_dir.path().join("file2.txt"),
+            temp_dir.path().join("subdir"),
+        ];
+
+        let path_list = PathList::from_iter(paths);
+        assert_eq!(path_list.items.len(), 3);
+        assert_eq!(path_list.state.selected(), None);
+    }
+
+    #[test]
+    fn test_pathlist_from_iter_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("alpha.txt"), "content").unwrap();
+        fs::create_dir(temp_dir.path().join("beta")).unwrap();
+
+        let entries: Vec<_> = fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|e| Entry::from_dir_entry(&e.unwrap()).unwrap())
+            .collect();
+
+        let path_list = PathList::from_iter(entries);
+        assert_eq!(path_list.items.len(), 2);
+
+        let names: Vec<&str> = path_list.items.iter().map(|p| p.value.as_str()).collect();
+        assert!(names.contains(&"alpha.txt"));
+        assert!(names.contains(&"beta"));
+        let beta = path_list.items.iter().find(|p| p.value == "beta").unwrap();
+        assert_eq!(beta.kind, ObjectType::Directory);
+    }
+
+    #[test]
+    fn test_pathlist_initial_state_no_selection() {
+        let paths: Vec<PathBuf> = vec![];
+        let path_list = PathList::from_iter(paths);
+        assert_eq!(path_list.state.selected(), None);
+    }
+
+    #[test]
+    fn test_app_select_first() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("file1.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("file2.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.select_first();
+        assert_eq!(app.path_list.state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_app_select_navigation() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("file1.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("file2.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("file3.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.select_first();
+        assert_eq!(app.path_list.state.selected(), Some(0));
+
+        app.select_next();
+        assert_eq!(app.path_list.state.selected(), Some(1));
+
+        app.select_next();
+        assert_eq!(app.path_list.state.selected(), Some(2));
+
+        app.select_previous();
+        assert_eq!(app.path_list.state.selected(), Some(1));
+
+        app.select_previous();
+        assert_eq!(app.path_list.state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_app_select_none() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.select_first();
+        assert_eq!(app.path_list.state.selected(), Some(0));
+        app.select_none();
+        assert_eq!(app.path_list.state.selected(), None);
+    }
+
+    #[test]
+    fn test_app_update_command_with_selection() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.select_first();
+        app.update_command(Command::Edit, false);
+
+        assert_eq!(app.output.command, Command::Edit);
+        assert_eq!(app.output.items.len(), 1);
+        assert!(app.output.items[0].path.ends_with("file.txt"));
+        assert_eq!(app.output.items[0].kind, ObjectType::File);
+        assert_eq!(
+            app.output.items[0].path.parent(),
+            Some(app.explorer.current_dir.as_path())
+        );
+        assert!(!app.should_exit);
+    }
+
+    #[test]
+    fn test_app_stale_selection_is_clamped() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("dir")).unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        // The list shrank without the cursor being moved back.
+        app.path_list.state.select(Some(5));
+        app.update_command(Command::Edit, false);
+        assert!(app.output.items[0].path.ends_with("dir"));
+
+        app.path_list.state.select(Some(5));
+        app.enter_directory().unwrap();
+    }
+
+    #[test]
+    fn test_app_update_command_with_quit() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.select_first();
+        app.update_command(Command::Edit, true);
+
+        assert!(app.should_exit);
+    }
+
+    #[test]
+    fn test_app_terminate_leaves_with_no_op() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.update_command(Command::Select, false);
+        app.terminate();
+        assert!(app.should_exit);
+        assert_eq!(app.output.command, Command::Noop);
+        assert!(app.output.items.is_empty());
+        assert_eq!(app.output.outcome(), Outcome::Cancelled);
+    }
+
+    #[test]
+    fn test_app_update_command_without_selection() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.update_command(Command::Edit, false);
+
+        assert_eq!(app.output.command, Command::Noop);
+        assert_eq!(app.output.items.len(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_app_update_command_preserves_non_utf8_names() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let name = OsString::from_vec(b"caf\xe9.txt".to_vec());
+        fs::write(temp_dir.path().join(&name), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.select_first();
+        app.update_command(Command::Select, false);
+
+        assert_eq!(app.output.items[0].path.file_name(), Some(name.as_os_str()));
+    }
+
+    #[test]
+    fn test_app_update_command_records_filter() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("alpha.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("beta.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.filter_paths('b');
+        settle(&mut app);
+        app.update_command(Command::Select, false);
+
+        assert_eq!(app.output.filter, "b");
+        assert!(app.output.items[0].path.ends_with("beta.txt"));
+    }
+
+    #[test]
+    fn test_app_quit_sets_quit_command() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.select_first();
+        app.update_command(Command::Select, false);
+        app.quit();
+
+        assert_eq!(app.output.command, Command::Quit);
+        assert!(app.output.items.is_empty());
+        assert!(app.should_exit);
+    }
+
+    #[test]
+    fn test_app_change_directory_and_exit() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.change_directory_and_exit();
+
+        assert_eq!(app.output.command, Command::Cd);
+        assert_eq!(app.output.items.len(), 1);
+        assert_eq!(app.output.items[0].path, app.explorer.current_dir);
+        assert!(app.should_exit);
+    }
+
+    #[test]
+    fn test_app_session_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("alpha.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("alps.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("beta.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.filter_paths('a');
+        app.filter_paths('l');
+        settle(&mut app);
+        app.select_next();
+        let session = app.session();
+        assert_eq!(session.filter, "al");
+        let selected = session.selected.clone().unwrap();
+
+        let explorer = Explorer::new(session.cwd.clone()).unwrap();
+        let mut restored = App::new(&handle, explorer).unwrap();
+        settle(&mut restored);
+        restored.restore(&session);
+        settle(&mut restored);
+
+        assert_eq!(restored.filter_string, "al");
+        assert_eq!(restored.path_count(), 2);
+        let i = restored.path_list.state.selected().unwrap();
+        assert_eq!(restored.path(i).unwrap().raw, selected);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_app_restore_selects_once_the_listing_arrives() {
+        use std::os::unix::ffi::OsStringExt;
+        let temp_dir = TempDir::new().unwrap();
+        let name = OsString::from_vec(b"caf\xe9".to_vec());
+        fs::write(temp_dir.path().join("cafe"), "content").unwrap();
+        fs::write(temp_dir.path().join(&name), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        // Pretend the listing is still being read.
+        let loader = Loader::start(temp_dir.path().to_path_buf(), ListOptions::default());
+        app.loading = Some(Loading::new(loader, None));
+        app.path_list = PathList::default();
+
+        let session = Session {
+            cwd: temp_dir.path().to_path_buf(),
+            filter: String::new(),
+            selected: Some(name.clone()),
+        };
+        app.restore(&session);
+        assert_eq!(app.session().selected, Some(name.clone()));
+        while app.loading.is_some() {
+            app.poll_load(Duration::from_millis(10)).unwrap();
+        }
+        let i = app.selected_index().unwrap();
+        assert_eq!(app.path(i).unwrap().raw, name);
+        assert_eq!(app.session().selected, Some(name));
+    }
+
+    #[test]
+    fn test_app_starts_in_insert_mode_and_letters_filter() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("quux.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("other.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        assert_eq!(app.mode, Mode::Insert);
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('q'))).unwrap();
+        settle(&mut app);
+        assert_eq!(app.filter_string, "q");
+        assert!(!app.should_exit);
+        assert_eq!(app.path_count(), 1);
+    }
+
+    #[test]
+    fn test_app_normal_mode_letters_are_commands() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("b.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+        assert_eq!(app.mode, Mode::Normal);
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('j'))).unwrap();
+        assert_eq!(app.path_list.state.selected(), Some(0));
+        app.handle_key(KeyEvent::from(KeyCode::Char('j'))).unwrap();
+        assert_eq!(app.path_list.state.selected(), Some(1));
+        assert!(app.filter_string.is_empty());
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('e'))).unwrap();
+        assert!(app.should_exit);
+        assert_eq!(app.output.command, Command::Edit);
+        assert!(app.output.items[0].path.ends_with("b.txt"));
+    }
+
+    #[test]
+    fn test_app_normal_mode_escape_clears_filter_but_does_not_exit() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('f'))).unwrap();
+        app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(app.filter_string, "f");
+
+        app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+        assert!(app.filter_string.is_empty());
+        assert!(!app.should_exit);
+
+        app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+        assert_eq!(app.mode, Mode::Normal);
+        assert!(!app.should_exit);
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('c'))).unwrap();
+        assert!(app.should_exit);
+        assert_eq!(app.output.command, Command::Cd);
+    }
+
+    #[test]
+    fn test_app_slash_starts_new_filter_in_insert_mode() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('f'))).unwrap();
+        app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+        app.handle_key(KeyEvent::from(KeyCode::Char('/'))).unwrap();
+        assert_eq!(app.mode, Mode::Insert);
+        assert!(app.filter_string.is_empty());
+    }
+
+    #[test]
+    fn test_app_marks_are_emitted_together() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("b.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("c.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.select_first();
+        app.perform(Action::ToggleMark).unwrap();
+        assert_eq!(app.path_list.state.selected(), Some(1));
+        app.select_next();
+        app.perform(Action::ToggleMark).unwrap();
+        app.update_command(Command::Edit, true);
+
+        assert_eq!(app.output.items.len(), 2);
+        assert!(app.output.items[0].path.ends_with("a.txt"));
+        assert!(app.output.items[1].path.ends_with("c.txt"));
+    }
+
+    #[test]
+    fn test_app_marks_persist_across_filter_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("apple.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("banana.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.filter_paths('b');
+        app.filter_paths('a');
+        app.filter_paths('n');
+        settle(&mut app);
+        app.perform(Action::ToggleMark).unwrap();
+        app.clear_filter();
+        settle(&mut app);
+
+        assert_eq!(app.path_count(), 2);
+        assert!(!app.is_marked(0));
+        assert!(app.is_marked(1));
+        assert_eq!(app.selection.len(), 1);
+    }
+
+    #[test]
+    fn test_app_visual_mode_marks_range() {
+        let temp_dir = TempDir::new().unwrap();
+        for name in ["a", "b", "c", "d"] {
+            fs::write(temp_dir.path().join(name), "content").unwrap();
+        }
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.path_list.state.select(Some(1));
+        app.mode = Mode::Normal;
+        app.perform(Action::StartVisual).unwrap();
+        assert_eq!(app.mode, Mode::Visual);
+        app.select_next();
+        app.select_next();
+        assert!(app.is_marked(3));
+        assert!(app.selection.is_empty());
+
+        app.handle_key(KeyEvent::from(KeyCode::Enter)).unwrap();
+        assert_eq!(app.mode, Mode::Normal);
+        let names: Vec<_> = app
+            .output
+            .items
+            .iter()
+            .map(|item| item.path.file_name().unwrap().to_owned())
+            .collect();
+        assert_eq!(names, ["b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_app_mark_all_and_invert_apply_to_visible_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("apple.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("banana.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("cherry.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.perform(Action::MarkAll).unwrap();
+        assert_eq!(app.selection.len(), 3);
+
+        app.filter_paths('c');
+        app.filter_paths('h');
+        settle(&mut app);
+        app.perform(Action::InvertMarks).unwrap();
+        assert_eq!(app.selection.len(), 2);
+        app.clear_filter();
+        settle(&mut app);
+        assert!(app.is_marked(0));
+        assert!(!app.is_marked(2));
+
+        app.perform(Action::ClearMarks).unwrap();
+        assert!(app.selection.is_empty());
     }
 
     #[test]
-    fn test_output_display_no_items() {
-        let output = Output::new("/test/path".to_string());
-        assert_eq!(format!("{}", output), "/test/path no-op ");
+    fn test_app_auto_cd_enters_single_matching_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("docs")).unwrap();
+        fs::create_dir(temp_dir.path().join("src")).unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        app.auto_cd = Some(AutoCd::new(std::time::Duration::ZERO));
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('d'))).unwrap();
+        settle(&mut app);
+        assert!(app.explorer.current_dir.ends_with("docs"));
+        assert!(app.filter_string.is_empty());
     }
 
     #[test]
-    fn test_output_display_with_single_item() {
-        let mut output = Output::new("/test/path".to_string());
-        output.command = "select".to_string();
-        output.items = vec!["/test/path/file.txt".to_string()];
-        assert_eq!(format!("{}", output), "/test/path select /test/path/file.txt");
+    fn test_app_auto_cd_waits_for_delay_and_ignores_files() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("docs")).unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        app.auto_cd = Some(AutoCd::new(std::time::Duration::from_secs(60)));
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('n'))).unwrap();
+        settle(&mut app);
+        assert!(app.timeout().is_none());
+
+        app.handle_key(KeyEvent::from(KeyCode::Backspace)).unwrap();
+        app.handle_key(KeyEvent::from(KeyCode::Char('d'))).unwrap();
+        settle(&mut app);
+        assert!(app.timeout().is_some());
+        assert_eq!(app.filter_string, "d");
+
+        app.auto_enter_directory().unwrap();
+        assert!(app.explorer.current_dir.ends_with("docs"));
     }
 
     #[test]
-    fn test_output_display_with_multiple_items() {
-        let mut output = Output::new("/test/path".to_string());
-        output.command = "select".to_string();
-        output.items = vec![
-            "/test/path/file1.txt".to_string(),
-            "/test/path/file2.txt".to_string(),
-            "/test/path/file3.txt".to_string(),
-        ];
-        assert_eq!(
-            format!("{}", output),
-            "/test/path select /test/path/file1.txt /test/path/file2.txt /test/path/file3.txt"
+    fn test_app_auto_cd_waits_for_matcher_to_finish() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("docs")).unwrap();
+        fs::create_dir(temp_dir.path().join("drafts")).unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        app.auto_cd = Some(AutoCd::new(std::time::Duration::from_secs(60)));
+        app.handle_key(KeyEvent::from(KeyCode::Char('d'))).unwrap();
+        app.handle_key(KeyEvent::from(KeyCode::Char('o'))).unwrap();
+        settle(&mut app);
+        assert!(
+            app.auto_cd
+                .as_ref()
+                .unwrap()
+                .timeout(Instant::now())
+                .is_some()
         );
-    }
 
-    #[test]
-    fn test_output_display_with_spaces_in_paths() {
-        let mut output = Output::new("/test/path with spaces".to_string());
-        output.command = "select".to_string();
-        output.items = vec!["/test/path with spaces/file with spaces.txt".to_string()];
-        assert_eq!(
-            format!("{}", output),
-            "/test/path with spaces select /test/path with spaces/file with spaces.txt"
+        // Widen the filter without giving the matcher time: the listing still
+        // shows only `docs`, which must not arm auto-cd.
+        app.filter_string.pop();
+        app.fuzzy.set_query(&app.filter_string);
+        app.arm_auto_cd().unwrap();
+        assert_eq!(app.path_count(), 1);
+        assert!(
+            app.auto_cd
+                .as_ref()
+                .unwrap()
+                .timeout(Instant::now())
+                .is_none()
+        );
+
+        while app.fuzzy.is_running() {
+            app.tick();
+        }
+        assert_eq!(app.path_count(), 2);
+        assert!(
+            app.auto_cd
+                .as_ref()
+                .unwrap()
+                .timeout(Instant::now())
+                .is_none()
+        );
+
+        // Narrowing again arms it once the matcher has caught up.
+        app.filter_string.push('r');
+        app.fuzzy.set_query(&app.filter_string);
+        app.arm_auto_cd().unwrap();
+        assert!(
+            app.auto_cd
+                .as_ref()
+                .unwrap()
+                .timeout(Instant::now())
+                .is_none()
+        );
+        while app.fuzzy.is_running() {
+            app.tick();
+        }
+        assert_eq!(app.path_count(), 1);
+        assert!(
+            app.auto_cd
+                .as_ref()
+                .unwrap()
+                .timeout(Instant::now())
+                .is_some()
         );
     }
 
     #[test]
-    fn test_object_type_from_directory() {
+    fn test_app_toggle_hidden_keeps_cursor_on_entry() {
         let temp_dir = TempDir::new().unwrap();
-        let path_buf = temp_dir.path().to_path_buf();
-        let obj_type = ObjectType::from(path_buf);
-        matches!(obj_type, ObjectType::Directory);
+        fs::write(temp_dir.path().join(".env"), "content").unwrap();
+        fs::write(temp_dir.path().join("a.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("b.txt"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        assert_eq!(app.path_count(), 2);
+
+        app.path_list.state.select(Some(1));
+        app.perform(Action::ToggleHidden).unwrap();
+        settle(&mut app);
+        assert_eq!(app.path_count(), 3);
+        assert_eq!(app.path_list.state.selected(), Some(2));
+        assert_eq!(app.path(2).unwrap().value, "b.txt");
+
+        app.perform(Action::ToggleHidden).unwrap();
+        settle(&mut app);
+        assert_eq!(app.path_count(), 2);
+        assert_eq!(app.path_list.state.selected(), Some(1));
     }
 
     #[test]
-    fn test_object_type_from_file() {
+    fn test_app_toggle_hidden_keeps_filter() {
         let temp_dir = TempDir::new().unwrap();
-        let file_path = temp_dir.path().join("test.txt");
-        fs::write(&file_path, "content").unwrap();
-        let obj_type = ObjectType::from(file_path);
-        matches!(obj_type, ObjectType::File);
+        fs::write(temp_dir.path().join(".env"), "content").unwrap();
+        fs::write(temp_dir.path().join("env.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("other"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.filter_paths('e');
+        app.filter_paths('n');
+        app.filter_paths('v');
+        settle(&mut app);
+        assert_eq!(app.path_count(), 1);
+
+        app.toggle_hidden().unwrap();
+        settle(&mut app);
+        assert_eq!(app.filter_string, "env");
+        assert_eq!(app.path_count(), 2);
     }
 
     #[test]
-    fn test_path_new() {
-        let path = Path::new("test.txt".to_string(), ObjectType::File);
-        assert_eq!(path.value, "test.txt");
-        matches!(path.kind, ObjectType::File);
+    fn test_app_sort_keys_reorder_listing() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("Zebra"), "content").unwrap();
+        fs::write(temp_dir.path().join("apple"), "content").unwrap();
+
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        assert_eq!(app.path(0).unwrap().value, "Zebra");
+
+        app.perform(Action::ReverseSort).unwrap();
+        settle(&mut app);
+        assert_eq!(app.path(0).unwrap().value, "apple");
+        assert_eq!(app.path_list.state.selected(), Some(0));
+
+        app.perform(Action::CycleSort).unwrap();
+        app.perform(Action::CycleSort).unwrap();
+        settle(&mut app);
+        assert_eq!(
+            app.explorer.options.sort.key,
+            breeze_cli::fs::SortKey::IgnoreCase
+        );
+        assert_eq!(app.path(0).unwrap().value, "Zebra");
     }
 
     #[test]
-    fn test_pathlist_from_iter_pathbufs() {
+    fn test_app_long_listing_renders_detail_columns() {
         let temp_dir = TempDir::new().unwrap();
-        fs::write(temp_dir.path().join("file1.txt"), "content").unwrap();
-        fs::write(temp_dir.path().join("file2.txt"), "content").unwrap();
-        fs::create_dir(temp_dir.path().join("subdir")).unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "content").unwrap();
 
-        let paths: Vec<PathBuf> = vec![
-            temp_dir.path().join("file1.txt"),
-            temp_dir.path().join("file2.txt"),
-            temp_dir.path().join("subdir"),
-        ];
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        app.details = Details::new(
+            vec![breeze_cli::cli::columns::Column::Size],
+            Default::default(),
+        );
 
-        let path_list = PathList::from_iter(paths);
-        assert_eq!(path_list.items.len(), 3);
-        assert_eq!(path_list.state.selected(), None);
+        let row = |app: &mut App| {
+            let mut buf = Buffer::empty(Rect::new(0, 0, 60, 6));
+            app.render(buf.area, &mut buf);
+            (0..60)
+                .map(|x| buf[(x, 3)].symbol().to_string())
+                .collect::<String>()
+        };
+        assert!(row(&mut app).starts_with("  notes.txt"));
+
+        app.perform(Action::ToggleLongListing).unwrap();
+        settle(&mut app);
+        assert!(row(&mut app).starts_with("  7B notes.txt"));
     }
 
     #[test]
-    fn test_pathlist_from_iter_dir_entries() {
+    fn test_app_renders_only_the_rows_in_view() {
         let temp_dir = TempDir::new().unwrap();
-        fs::write(temp_dir.path().join("alpha.txt"), "content").unwrap();
-        fs::write(temp_dir.path().join("beta.txt"), "content").unwrap();
+        for i in 0..40 {
+            fs::write(temp_dir.path().join(format!("f{i:02}")), "").unwrap();
+        }
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        let row = |buf: &Buffer, y| -> String { (0..60).map(|x| buf[(x, y)].symbol()).collect() };
+
+        // Rows 3 to 6 hold the list.
+        let mut buf = Buffer::empty(Rect::new(0, 0, 60, 8));
+        app.path_list.state.select(Some(30));
+        app.render(buf.area, &mut buf);
+        assert!(row(&buf, 3).contains("f27"), "{}", row(&buf, 3));
+        assert!(row(&buf, 6).starts_with(">") && row(&buf, 6).contains("f30"));
+
+        app.select_last();
+        app.render(buf.area, &mut buf);
+        assert!(row(&buf, 6).contains("f39"));
+        assert_eq!(app.path_list.state.selected(), Some(39));
+
+        app.filter_paths('3');
+        app.filter_paths('9');
+        settle(&mut app);
+        app.render(buf.area, &mut buf);
+        assert!(row(&buf, 3).contains("f39"));
+        assert_eq!(buf[(3, 3)].modifier, Modifier::BOLD);
+    }
 
-        let entries: Vec<_> = fs::read_dir(temp_dir.path())
-            .unwrap()
-            .map(|e| e.unwrap())
+    #[cfg(unix)]
+    #[test]
+    fn test_app_symlinks_show_target_and_can_be_followed() {
+        let temp_dir = TempDir::new().unwrap();
+        let real = temp_dir.path().join("real");
+        fs::create_dir(&real).unwrap();
+        fs::write(real.join("target.txt"), "content").unwrap();
+        fs::write(real.join("zzz.txt"), "content").unwrap();
+        let links = temp_dir.path().join("links");
+        fs::create_dir(&links).unwrap();
+        std::os::unix::fs::symlink(real.join("zzz.txt"), links.join("file-link")).unwrap();
+        std::os::unix::fs::symlink("../real", links.join("dir-link")).unwrap();
+        std::os::unix::fs::symlink("missing", links.join("broken")).unwrap();
+
+        let explorer = Explorer::new(links.clone()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        let kinds: Vec<_> = (0..app.path_count())
+            .map(|i| app.path(i).unwrap().kind)
             .collect();
+        assert_eq!(
+            kinds,
+            [
+                ObjectType::BrokenSymlink,
+                ObjectType::Symlink { to_dir: true },
+                ObjectType::Symlink { to_dir: false },
+            ]
+        );
 
-        let path_list = PathList::from_iter(entries);
-        assert_eq!(path_list.items.len(), 2);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 60, 8));
+        app.render(buf.area, &mut buf);
+        let row: String = (0..60).map(|x| buf[(x, 4)].symbol().to_string()).collect();
+        assert!(row.contains("dir-link -> ../real"), "{row}");
 
-        let names: Vec<&str> = path_list
-            .items
-            .iter()
-            .map(|p| p.value.as_str())
-            .collect();
-        assert!(names.contains(&"alpha.txt"));
-        assert!(names.contains(&"beta.txt"));
+        app.path_list.state.select(Some(2));
+        app.perform(Action::FollowLink).unwrap();
+        settle(&mut app);
+        assert_eq!(app.explorer.current_dir, real.canonicalize().unwrap());
+        assert_eq!(app.path_list.state.selected(), Some(1));
+        assert_eq!(app.path(1).unwrap().value, "zzz.txt");
+
+        let mut app = App::new(&handle, Explorer::new(links).unwrap()).unwrap();
+        settle(&mut app);
+        app.path_list.state.select(Some(0));
+        app.perform(Action::FollowLink).unwrap();
+        assert!(!app.messages.has_errors());
+        let message = app.messages.current(Instant::now()).unwrap();
+        assert_eq!(message.severity, Severity::Warn);
+        assert_eq!(
+            message.text,
+            "broken points to missing, which does not exist"
+        );
+        app.path_list.state.select(Some(1));
+        app.perform(Action::EnterDirectory).unwrap();
+        settle(&mut app);
+        assert_eq!(app.path_count(), 2);
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_pathlist_initial_state_no_selection() {
-        let paths: Vec<PathBuf> = vec![];
-        let path_list = PathList::from_iter(paths);
-        assert_eq!(path_list.state.selected(), None);
+    fn test_app_logical_mode_reports_path_as_navigated() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir(root.join("real")).unwrap();
+        std::os::unix::fs::symlink(root.join("real"), root.join("link")).unwrap();
+
+        let explorer =
+            Explorer::with_path_mode(root.clone(), breeze_cli::PathMode::Logical).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        app.path_list.state.select(Some(0));
+        assert_eq!(app.path(0).unwrap().value, "link");
+        app.perform(Action::EnterDirectory).unwrap();
+        settle(&mut app);
+        app.perform(Action::ChangeDirectoryAndExit).unwrap();
+        assert_eq!(app.output.items[0].path, root.join("link"));
+
+        app.perform(Action::ChangeToParent).unwrap();
+        settle(&mut app);
+        assert_eq!(app.explorer.current_dir, root);
     }
 
     #[test]
-    fn test_app_select_first() {
+    fn test_app_loading_directory_enters_once_opened() {
         let temp_dir = TempDir::new().unwrap();
-        fs::write(temp_dir.path().join("file1.txt"), "content").unwrap();
-        fs::write(temp_dir.path().join("file2.txt"), "content").unwrap();
+        fs::create_dir(temp_dir.path().join("subdir")).unwrap();
+        fs::write(temp_dir.path().join("subdir/inner.txt"), "").unwrap();
 
         let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
-        let cwd = explorer.cwd();
-        let paths = explorer.ls().unwrap();
         let handle = stderr();
-
-        let mut app = App {
-            handle: &handle,
-            should_exit: false,
-            path_list: PathList::from_iter(paths),
-            explorer: explorer,
-            output: Output::new(cwd),
-            matcher: Matcher::new(Config::DEFAULT.match_paths()),
-            pattern: None,
-            filter_string: String::new(),
-        };
-
-        app.select_first();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        let before = app.explorer.current_dir.clone();
+
+        let loader = app.explorer.start_cd(PathBuf::from("subdir")).unwrap();
+        app.loading = Some(Loading::new(loader, None));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 60, 8));
+        app.render(buf.area, &mut buf);
+        let title: String = (0..60).map(|x| buf[(x, 2)].symbol().to_string()).collect();
+        assert!(title.contains("| opening "), "{title}");
+        assert_eq!(app.explorer.current_dir, before);
+        assert!(app.timeout().is_some());
+
+        app.poll_load(Duration::from_secs(5)).unwrap();
+        assert!(app.loading.is_none());
+        assert_eq!(app.explorer.current_dir, before.join("subdir"));
+        assert_eq!(app.path(0).unwrap().value, "inner.txt");
         assert_eq!(app.path_list.state.selected(), Some(0));
     }
 
     #[test]
-    fn test_app_select_navigation() {
+    fn test_app_lists_entries_as_they_arrive_then_sorted() {
         let temp_dir = TempDir::new().unwrap();
-        fs::write(temp_dir.path().join("file1.txt"), "content").unwrap();
-        fs::write(temp_dir.path().join("file2.txt"), "content").unwrap();
-        fs::write(temp_dir.path().join("file3.txt"), "content").unwrap();
+        // Enough entries for several batches.
+        for i in (0..1500).rev() {
+            fs::write(temp_dir.path().join(format!("f{i:04}")), "").unwrap();
+        }
 
         let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
-        let cwd = explorer.cwd();
-        let paths = explorer.ls().unwrap();
         let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        let loader = Loader::start(temp_dir.path().to_path_buf(), ListOptions::default());
+        app.loading = Some(Loading::new(loader, Some("f0700".into())));
+        app.poll_load(Duration::from_millis(1)).unwrap();
+        while let Some(loading) = &app.loading {
+            // Once the directory opens, every entry read so far is listed.
+            if loading.loader.is_opened() {
+                assert_eq!(app.fuzzy.len(), loading.shown);
+            }
+            app.poll_load(Duration::from_millis(1)).unwrap();
+        }
 
-        let mut app = App {
-            handle: &handle,
-            should_exit: false,
-            path_list: PathList::from_iter(paths),
-            explorer: explorer,
-            output: Output::new(cwd),
-            matcher: Matcher::new(Config::DEFAULT.match_paths()),
-            pattern: None,
-            filter_string: String::new(),
-        };
-
-        app.select_first();
-        assert_eq!(app.path_list.state.selected(), Some(0));
-
-        app.select_next();
-        assert_eq!(app.path_list.state.selected(), Some(1));
-
-        app.select_next();
-        assert_eq!(app.path_list.state.selected(), Some(2));
+        let expected: Vec<String> = (0..1500).map(|i| format!("f{i:04}")).collect();
+        assert_eq!(values(&app), expected);
+        let i = app.selected_index().unwrap();
+        assert_eq!(app.path(i).unwrap().value, "f0700");
+    }
 
-        app.select_previous();
-        assert_eq!(app.path_list.state.selected(), Some(1));
+    #[test]
+    fn test_app_escape_cancels_loading() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("subdir")).unwrap();
+        fs::write(temp_dir.path().join("file.txt"), "").unwrap();
 
-        app.select_previous();
-        assert_eq!(app.path_list.state.selected(), Some(0));
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        let before = app.explorer.current_dir.clone();
+
+        let loader = app.explorer.start_cd(PathBuf::from("subdir")).unwrap();
+        app.loading = Some(Loading::new(loader, None));
+        app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+        assert!(app.loading.is_none());
+        assert_eq!(app.mode, Mode::Insert);
+        assert_eq!(app.explorer.current_dir, before);
+        assert_eq!(app.path_count(), 2);
+        let message = app.messages.current(Instant::now()).unwrap();
+        assert!(message.text.starts_with("Cancelled opening"));
     }
 
     #[test]
-    fn test_app_select_none() {
+    fn test_app_failed_enter_keeps_current_listing() {
         let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("gone")).unwrap();
         fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
 
         let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
-        let cwd = explorer.cwd();
-        let paths = explorer.ls().unwrap();
         let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        let before = app.explorer.current_dir.clone();
+
+        fs::remove_dir(temp_dir.path().join("gone")).unwrap();
+        app.path_list.state.select(Some(1));
+        assert_eq!(app.path(1).unwrap().value, "gone");
+        assert!(app.enter_directory().is_err());
+        assert_eq!(app.explorer.current_dir, before);
+        assert_eq!(app.path_count(), 2);
+    }
 
-        let mut app = App {
-            handle: &handle,
-            should_exit: false,
-            path_list: PathList::from_iter(paths),
-            explorer: explorer,
-            output: Output::new(cwd),
-            matcher: Matcher::new(Config::DEFAULT.match_paths()),
-            pattern: None,
-            filter_string: String::new(),
-        };
+    #[test]
+    fn test_app_reports_permission_denied_as_warning() {
+        let temp_dir = TempDir::new().unwrap();
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        app.report(ExplorerError::PermissionDenied("/secret".to_string()));
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 60, 8));
+        app.render(buf.area, &mut buf);
+        let row: String = (0..60).map(|x| buf[(x, 6)].symbol().to_string()).collect();
+        assert!(row.starts_with("Permission denied: /secret"), "{row}");
+        assert_eq!(buf[(0, 6)].fg, STATUS_WARN_STYLE.fg.unwrap());
+
+        // The message outlives key presses and only goes away once it expires.
+        app.handle_key(KeyEvent::from(KeyCode::Down)).unwrap();
+        assert!(app.messages.current(Instant::now()).is_some());
+        assert!(!app.messages.has_errors());
+    }
 
-        app.select_first();
-        assert_eq!(app.path_list.state.selected(), Some(0));
-        app.select_none();
-        assert_eq!(app.path_list.state.selected(), None);
+    #[test]
+    fn test_app_status_line_expires() {
+        let temp_dir = TempDir::new().unwrap();
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        let long_ago = Instant::now() - Duration::from_secs(60);
+        app.messages.push(Severity::Error, "old news", long_ago);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 60, 8));
+        app.render(buf.area, &mut buf);
+        let text: String = buf.content().iter().map(|c| c.symbol()).collect();
+        assert!(!text.contains("old news"));
+        assert!(app.timeout().is_none());
     }
 
     #[test]
-    fn test_app_update_command_with_selection() {
+    fn test_app_outcome_keeps_result_despite_earlier_errors() {
         let temp_dir = TempDir::new().unwrap();
         fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        app.messages.error("transient");
+        assert_eq!(app.outcome(), Outcome::Error);
+
+        app.select_first();
+        app.update_command(Command::Select, true);
+        assert_eq!(app.outcome(), Outcome::Selected);
+
+        app.last_key_failed = true;
+        assert_eq!(app.outcome(), Outcome::Error);
+    }
 
+    #[test]
+    fn test_app_reports_errors_with_selection() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("gone")).unwrap();
         let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
-        let cwd = explorer.cwd();
-        let paths = explorer.ls().unwrap();
         let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+
+        fs::remove_dir(temp_dir.path().join("gone")).unwrap();
+        app.path_list.state.select(Some(0));
+        let err = app.handle_key(KeyEvent::from(KeyCode::Right)).unwrap_err();
+        app.report(err);
+
+        assert!(app.messages.has_errors());
+        let message = app.messages.current(Instant::now()).unwrap();
+        assert_eq!(message.severity, Severity::Error);
+        assert!(message.text.contains("\"gone\""), "{}", message.text);
+    }
 
-        let mut app = App {
-            handle: &handle,
-            should_exit: false,
-            path_list: PathList::from_iter(paths),
-            explorer: explorer,
-            output: Output::new(cwd.clone()),
-            matcher: Matcher::new(Config::DEFAULT.match_paths()),
-            pattern: None,
-            filter_string: String::new(),
-        };
+    #[test]
+    fn test_app_info_messages() {
+        let temp_dir = TempDir::new().unwrap();
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        app.mode = Mode::Normal;
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('.'))).unwrap();
+        let message = app.messages.current(Instant::now()).unwrap();
+        assert_eq!(message.severity, Severity::Info);
+        assert_eq!(message.text, "Hidden files shown");
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('s'))).unwrap();
+        let message = app.messages.current(Instant::now()).unwrap();
+        assert_eq!(message.text, "Sorted by natural");
+    }
 
-        app.select_first();
-        app.update_command("test-cmd".to_string(), false);
+    #[test]
+    fn test_app_message_log() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("b.txt"), "content").unwrap();
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        app.mode = Mode::Normal;
+        app.path_list.state.select(Some(0));
+        app.messages.warn("first");
+        app.messages.error("second");
+
+        app.handle_key(KeyEvent::from(KeyCode::Char('m'))).unwrap();
+        assert!(app.show_log);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 60, 10));
+        app.render(buf.area, &mut buf);
+        let rows: Vec<String> = (0..10)
+            .map(|y| (0..60).map(|x| buf[(x, y)].symbol().to_string()).collect())
+            .collect();
+        assert!(rows.iter().any(|r| r.contains("warn  first")), "{rows:#?}");
+        assert!(rows.iter().any(|r| r.contains("error second")), "{rows:#?}");
 
-        assert_eq!(app.output.command, "test-cmd");
-        assert_eq!(app.output.items.len(), 1);
-        assert!(app.output.items[0].ends_with("file.txt"));
+        // Keys go to the log while it is open.
+        app.handle_key(KeyEvent::from(KeyCode::Char('j'))).unwrap();
+        assert_eq!(app.path_list.state.selected(), Some(0));
+        app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+        assert!(!app.show_log);
         assert!(!app.should_exit);
     }
 
     #[test]
-    fn test_app_update_command_with_quit() {
+    fn test_app_ctrl_z_requests_suspend() {
         let temp_dir = TempDir::new().unwrap();
-        fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
-
         let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
-        let cwd = explorer.cwd();
-        let paths = explorer.ls().unwrap();
         let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
 
-        let mut app = App {
-            handle: &handle,
-            should_exit: false,
-            path_list: PathList::from_iter(paths),
-            explorer: explorer,
-            output: Output::new(cwd),
-            matcher: Matcher::new(Config::DEFAULT.match_paths()),
-            pattern: None,
-            filter_string: String::new(),
-        };
+        let ctrl_z = KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL);
+        app.handle_key(ctrl_z).unwrap();
+        assert!(app.signals.take_suspend());
+        assert!(!app.should_exit);
+    }
 
-        app.select_first();
-        app.update_command("test-cmd".to_string(), true);
+    #[test]
+    fn test_app_picker_lists_candidates() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("docs")).unwrap();
+        fs::write(temp_dir.path().join("ignored.txt"), "content").unwrap();
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        app.set_candidates(vec![
+            temp_dir.path().join("docs"),
+            PathBuf::from("/nowhere/my notes.txt"),
+            PathBuf::from("/nowhere/other.txt"),
+        ]);
+        assert_eq!(app.path_count(), 3);
+        assert_eq!(app.path(1).unwrap().value, "/nowhere/my notes.txt");
+
+        // The temporary directory's random name cannot match "s.".
+        app.handle_key(KeyEvent::from(KeyCode::Char('s'))).unwrap();
+        app.handle_key(KeyEvent::from(KeyCode::Char('.'))).unwrap();
+        settle(&mut app);
+        assert_eq!(app.path_count(), 1);
+        app.handle_key(KeyEvent::from(KeyCode::Backspace)).unwrap();
+        app.handle_key(KeyEvent::from(KeyCode::Backspace)).unwrap();
+        settle(&mut app);
+        assert_eq!(app.path_count(), 3);
+
+        // Directory navigation is off; the candidates stay listed.
+        app.handle_key(KeyEvent::from(KeyCode::Right)).unwrap();
+        app.handle_key(KeyEvent::from(KeyCode::Left)).unwrap();
+        assert_eq!(app.path_count(), 3);
+
+        app.path_list.state.select(Some(1));
+        app.handle_key(KeyEvent::from(KeyCode::Enter)).unwrap();
+        assert_eq!(app.output.command, Command::Select);
+        assert_eq!(
+            app.output.items,
+            vec![Item::new("/nowhere/my notes.txt", ObjectType::File)]
+        );
+    }
 
+    #[test]
+    fn test_app_picker_escape_quits() {
+        let temp_dir = TempDir::new().unwrap();
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        app.set_candidates(vec![PathBuf::from("a"), PathBuf::from("b")]);
+        app.mode = Mode::Normal;
+
+        app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
         assert!(app.should_exit);
+        assert_eq!(app.output.command, Command::Quit);
     }
 
     #[test]
-    fn test_app_update_command_without_selection() {
+    fn test_app_search_jumps_to_file_directory() {
         let temp_dir = TempDir::new().unwrap();
-        fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+        fs::create_dir_all(temp_dir.path().join("src/core")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("target/debug")).unwrap();
+        fs::write(temp_dir.path().join("src/core/explorer.rs"), "").unwrap();
+        fs::write(temp_dir.path().join("src/core/modes.rs"), "").unwrap();
+        fs::write(temp_dir.path().join("target/debug/explorer.rs"), "").unwrap();
 
         let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
-        let cwd = explorer.cwd();
-        let paths = explorer.ls().unwrap();
         let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        app.mode = Mode::Normal;
+        app.handle_key(KeyEvent::from(KeyCode::Char('f'))).unwrap();
+        assert_eq!(app.mode, Mode::Insert);
+        settle(&mut app);
+        assert_eq!(app.path_count(), 4);
+
+        for c in "explorer".chars() {
+            app.handle_key(KeyEvent::from(KeyCode::Char(c))).unwrap();
+        }
+        settle(&mut app);
+        let values = values(&app);
+        assert_eq!(values, ["src/core/explorer.rs"]);
+        assert_eq!(
+            app.explorer.current_dir,
+            temp_dir.path().canonicalize().unwrap()
+        );
 
-        let mut app = App {
-            handle: &handle,
-            should_exit: false,
-            path_list: PathList::from_iter(paths),
-            explorer: explorer,
-            output: Output::new(cwd),
-            matcher: Matcher::new(Config::DEFAULT.match_paths()),
-            pattern: None,
-            filter_string: String::new(),
-        };
+        app.handle_key(KeyEvent::from(KeyCode::Enter)).unwrap();
+        settle(&mut app);
+        assert!(app.search.is_none());
+        assert!(!app.should_exit);
+        assert!(app.explorer.current_dir.ends_with("src/core"));
+        assert!(app.filter_string.is_empty());
+        let i = app.path_list.state.selected().unwrap();
+        assert_eq!(app.path(i).unwrap().value, "explorer.rs");
+    }
 
-        app.update_command("test-cmd".to_string(), false);
+    #[test]
+    fn test_app_search_marks_and_escape() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("docs")).unwrap();
+        fs::write(temp_dir.path().join("docs/guide.md"), "").unwrap();
+        fs::write(temp_dir.path().join("top.txt"), "").unwrap();
 
-        assert_eq!(app.output.command, "no-op");
-        assert_eq!(app.output.items.len(), 0);
+        let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
+        let handle = stderr();
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
+        let ctrl_f = KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL);
+        app.handle_key(ctrl_f).unwrap();
+        settle(&mut app);
+        for c in "guide".chars() {
+            app.handle_key(KeyEvent::from(KeyCode::Char(c))).unwrap();
+        }
+        settle(&mut app);
+        app.handle_key(KeyEvent::from(KeyCode::Tab)).unwrap();
+        let root = app.explorer.current_dir.clone();
+        assert!(app.selection.contains(&root.join("docs/guide.md")));
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 60, 8));
+        app.render(buf.area, &mut buf);
+        let title: String = (0..60).map(|x| buf[(x, 2)].symbol().to_string()).collect();
+        assert!(title.contains("| 3 paths"), "{title}");
+
+        app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+        app.handle_key(KeyEvent::from(KeyCode::Esc)).unwrap();
+        settle(&mut app);
+        assert!(app.search.is_none());
+        assert!(!app.should_exit);
+        assert_eq!(app.explorer.current_dir, root);
+        assert_eq!(app.path_count(), 2);
     }
 
     #[test]
@@ -861,27 +2698,18 @@ mod tests {
         fs::write(temp_dir.path().join("beta.txt"), "content").unwrap();
 
         let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
-        let cwd = explorer.cwd();
-        let paths = explorer.ls().unwrap();
         let handle = stderr();
 
-        let mut app = App {
-            handle: &handle,
-            should_exit: false,
-            path_list: PathList::from_iter(paths),
-            explorer: explorer,
-            output: Output::new(cwd),
-            matcher: Matcher::new(Config::DEFAULT.match_paths()),
-            pattern: None,
-            filter_string: String::new(),
-        };
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
 
         app.filter_string.push_str("alpha");
         app.clear_filter();
+        settle(&mut app);
 
         assert_eq!(app.filter_string, "");
-        assert!(app.pattern.is_none());
-        assert_eq!(app.path_list.items.len(), 2);
+        assert!(app.fuzzy.query().is_empty());
+        assert_eq!(app.path_count(), 2);
         assert_eq!(app.path_list.state.selected(), Some(0));
     }
 
@@ -893,29 +2721,20 @@ mod tests {
         fs::write(temp_dir.path().join("gamma.txt"), "content").unwrap();
 
         let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
-        let cwd = explorer.cwd();
-        let paths = explorer.ls().unwrap();
         let handle = stderr();
 
-        let mut app = App {
-            handle: &handle,
-            should_exit: false,
-            path_list: PathList::from_iter(paths),
-            explorer: explorer,
-            output: Output::new(cwd),
-            matcher: Matcher::new(Config::DEFAULT.match_paths()),
-            pattern: None,
-            filter_string: String::new(),
-        };
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
 
         app.filter_paths('a');
         app.filter_paths('l');
         app.filter_paths('p');
+        settle(&mut app);
 
         assert_eq!(app.filter_string, "alp");
-        assert!(app.pattern.is_some());
-        assert_eq!(app.path_list.items.len(), 1);
-        assert_eq!(app.path_list.items[0].value, "alpha.txt");
+        assert!(!app.fuzzy.query().is_empty());
+        assert_eq!(app.path_count(), 1);
+        assert_eq!(app.path(0).unwrap().value, "alpha.txt");
         assert_eq!(app.path_list.state.selected(), Some(0));
     }
 
@@ -927,33 +2746,19 @@ mod tests {
         fs::write(temp_dir.path().join("other.txt"), "content").unwrap();
 
         let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
-        let cwd = explorer.cwd();
-        let paths = explorer.ls().unwrap();
         let handle = stderr();
 
-        let mut app = App {
-            handle: &handle,
-            should_exit: false,
-            path_list: PathList::from_iter(paths),
-            explorer: explorer,
-            output: Output::new(cwd),
-            matcher: Matcher::new(Config::DEFAULT.match_paths()),
-            pattern: None,
-            filter_string: String::new(),
-        };
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
 
         app.filter_paths('t');
         app.filter_paths('e');
+        settle(&mut app);
 
-        assert!(app.path_list.items.len() >= 2);
-        let names: Vec<&str> = app
-            .path_list
-            .items
-            .iter()
-            .map(|p| p.value.as_str())
-            .collect();
-        assert!(names.contains(&"test1.txt"));
-        assert!(names.contains(&"test2.txt"));
+        assert!(app.path_count() >= 2);
+        let names = values(&app);
+        assert!(names.contains(&"test1.txt".to_string()));
+        assert!(names.contains(&"test2.txt".to_string()));
     }
 
     #[test]
@@ -963,26 +2768,17 @@ mod tests {
         fs::write(temp_dir.path().join("beta.txt"), "content").unwrap();
 
         let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
-        let cwd = explorer.cwd();
-        let paths = explorer.ls().unwrap();
         let handle = stderr();
 
-        let mut app = App {
-            handle: &handle,
-            should_exit: false,
-            path_list: PathList::from_iter(paths),
-            explorer: explorer,
-            output: Output::new(cwd),
-            matcher: Matcher::new(Config::DEFAULT.match_paths()),
-            pattern: None,
-            filter_string: String::new(),
-        };
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
 
         app.filter_paths('x');
         app.filter_paths('y');
         app.filter_paths('z');
+        settle(&mut app);
 
-        assert_eq!(app.path_list.items.len(), 0);
+        assert_eq!(app.path_count(), 0);
     }
 
     #[test]
@@ -991,24 +2787,14 @@ mod tests {
         fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
 
         let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
-        let cwd = explorer.cwd();
-        let paths = explorer.ls().unwrap();
         let handle = stderr();
 
-        let mut app = App {
-            handle: &handle,
-            should_exit: false,
-            path_list: PathList::from_iter(paths),
-            explorer: explorer,
-            output: Output::new(cwd),
-            matcher: Matcher::new(Config::DEFAULT.match_paths()),
-            pattern: None,
-            filter_string: String::new(),
-        };
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
 
         app.remove_last_char_from_filter();
         assert_eq!(app.filter_string, "");
-        assert!(app.pattern.is_none());
+        assert!(app.fuzzy.query().is_empty());
     }
 
     #[test]
@@ -1018,30 +2804,22 @@ mod tests {
         fs::write(temp_dir.path().join("beta.txt"), "content").unwrap();
 
         let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
-        let cwd = explorer.cwd();
-        let paths = explorer.ls().unwrap();
         let handle = stderr();
 
-        let mut app = App {
-            handle: &handle,
-            should_exit: false,
-            path_list: PathList::from_iter(paths),
-            explorer: explorer,
-            output: Output::new(cwd),
-            matcher: Matcher::new(Config::DEFAULT.match_paths()),
-            pattern: None,
-            filter_string: String::new(),
-        };
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
 
-        let initial_count = app.path_list.items.len();
+        let initial_count = app.path_count();
         app.filter_paths('a');
-        let filtered_count = app.path_list.items.len();
+        settle(&mut app);
+        let filtered_count = app.path_count();
         assert!(filtered_count <= initial_count);
 
         app.remove_last_char_from_filter();
+        settle(&mut app);
         assert_eq!(app.filter_string, "");
-        assert!(app.pattern.is_none());
-        assert_eq!(app.path_list.items.len(), initial_count);
+        assert!(app.fuzzy.query().is_empty());
+        assert_eq!(app.path_count(), initial_count);
     }
 
     #[test]
@@ -1051,30 +2829,22 @@ mod tests {
         fs::write(temp_dir.path().join("beta.txt"), "content").unwrap();
 
         let explorer = Explorer::new(temp_dir.path().to_path_buf()).unwrap();
-        let cwd = explorer.cwd();
-        let paths = explorer.ls().unwrap();
         let handle = stderr();
 
-        let mut app = App {
-            handle: &handle,
-            should_exit: false,
-            path_list: PathList::from_iter(paths),
-            explorer: explorer,
-            output: Output::new(cwd),
-            matcher: Matcher::new(Config::DEFAULT.match_paths()),
-            pattern: None,
-            filter_string: String::new(),
-        };
+        let mut app = App::new(&handle, explorer).unwrap();
+        settle(&mut app);
 
         app.filter_paths('a');
         app.filter_paths('l');
         app.filter_paths('p');
-        assert_eq!(app.path_list.items.len(), 1);
+        settle(&mut app);
+        assert_eq!(app.path_count(), 1);
 
         app.remove_last_char_from_filter();
+        settle(&mut app);
         assert_eq!(app.filter_string, "al");
-        assert!(app.pattern.is_some());
-        assert_eq!(app.path_list.items.len(), 1);
+        assert!(!app.fuzzy.query().is_empty());
+        assert_eq!(app.path_count(), 1);
     }
 
     #[test]
@@ -1188,11 +2958,8 @@ mod tests {
     #[test]
     fn test_path_with_match_indices_constructor() {
         let indices = vec![0, 2, 4];
-        let path = Path::with_match_indices(
-            "hello.txt".to_string(),
-            ObjectType::File,
-            indices.clone(),
-        );
+        let path =
+            Path::with_match_indices("hello.txt".to_string(), ObjectType::File, indices.clone());
 
         assert_eq!(path.value, "hello.txt");
         assert_eq!(path.match_indices, indices);